@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use rand::RngCore;
+
+use crate::robot::{Behavior, Robot};
+use crate::tasks::TaskKind;
+
+// ce qu'un comportement a le droit de voir du monde en plus de robot.belief
+// pas de carte globale : un robot ne décide qu'avec ce qu'il a lui-même observé
+pub struct WorldView {
+    pub station_position: (usize, usize),
+    pub tick: u64,
+}
+
+// l'intention du robot pour ce tick, c'est le robot qui l'exécute ensuite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Idle,
+    MoveTowards((usize, usize)),
+    MoveRandomly,
+    // se déplacer puis marquer la tuile d'arrivée comme explorée
+    ExploreTowards((usize, usize)),
+    Mine,
+    // un tick d'analyse du point d'intérêt sous le robot
+    Analyze,
+}
+
+// une stratégie de robot, les crates externes peuvent implémenter la leur et l'enregistrer
+pub trait RobotBehavior {
+    fn decide(&self, robot: &Robot, world: &WorldView, rng: &mut dyn RngCore) -> Action;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExplorationBehavior;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollectionBehavior;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScientificBehavior;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatrolBehavior;
+
+// on va à la frontière la plus proche, la limite entre ce qu'on connaît et l'inconnu.
+// Quand la carte est entièrement connue on patrouille, et on ne part au hasard que si on ne sait encore rien
+pub fn explore(robot: &Robot, rng: &mut dyn RngCore) -> Action {
+    if let Some(goal) = robot.belief.nearest_frontier(robot.position) {
+        return Action::ExploreTowards(goal);
+    }
+    if let Some(goal) = robot.belief.stalest_tile(robot.position) {
+        return Action::ExploreTowards(goal);
+    }
+    // on garde le même but tant qu'on ne l'a pas atteint, pour profiter du chemin en cache
+    match &robot.route {
+        Some(route) if !route.is_finished() => Action::ExploreTowards(route.goal),
+        _ => Action::ExploreTowards(robot.find_random_goal(rng)),
+    }
+}
+
+// la tâche donnée par la station passe avant les envies du robot, None si elle ne dit rien à faire
+pub fn follow_task(robot: &Robot, world: &WorldView) -> Option<Action> {
+    let task = robot.task?;
+    match task.kind {
+        TaskKind::ReturnCargo => Some(Action::MoveTowards(world.station_position)),
+        TaskKind::MineAt(position) | TaskKind::AnalysePoi(position) if robot.position != position => Some(Action::MoveTowards(position)),
+        TaskKind::MineAt(_) => robot.can_mine().then_some(Action::Mine),
+        TaskKind::AnalysePoi(_) => (robot.is_analyzing() || robot.can_analyze()).then_some(Action::Analyze),
+        TaskKind::ExploreRegion(centre) | TaskKind::Patrol(centre) => (robot.position != centre).then_some(Action::ExploreTowards(centre)),
+        // on s'arrête à côté : avec le trafic la tuile du robot en panne est occupée
+        TaskKind::Rescue(position) if robot.position.0.abs_diff(position.0) + robot.position.1.abs_diff(position.1) > 1 => {
+            Some(Action::MoveTowards(position))
+        }
+        TaskKind::Rescue(_) => Some(Action::Idle),
+    }
+}
+
+impl RobotBehavior for ExplorationBehavior {
+    fn decide(&self, robot: &Robot, world: &WorldView, rng: &mut dyn RngCore) -> Action {
+        follow_task(robot, world).unwrap_or_else(|| explore(robot, rng))
+    }
+}
+
+// on remplit la soute puis on rentre la vider à la station
+impl RobotBehavior for CollectionBehavior {
+    fn decide(&self, robot: &Robot, world: &WorldView, rng: &mut dyn RngCore) -> Action {
+        if robot.is_cargo_full() {
+            return Action::MoveTowards(world.station_position);
+        }
+        if let Some(action) = follow_task(robot, world) {
+            return action;
+        }
+        if robot.can_mine() {
+            return Action::Mine;
+        }
+        // aucun gisement connu : on part en chercher
+        match robot.find_resource() {
+            Some(goal) => Action::MoveTowards(goal),
+            None => explore(robot, rng),
+        }
+    }
+}
+
+// on analyse les points d'intérêt un par un et on rapporte chaque rapport à la station
+impl RobotBehavior for ScientificBehavior {
+    fn decide(&self, robot: &Robot, world: &WorldView, rng: &mut dyn RngCore) -> Action {
+        if robot.is_analyzing() || robot.can_analyze() {
+            return Action::Analyze;
+        }
+        if !robot.reports.is_empty() {
+            return Action::MoveTowards(world.station_position);
+        }
+        if let Some(action) = follow_task(robot, world) {
+            return action;
+        }
+        // sans module d'analyse, arrivé sur place il n'y a rien à faire de plus
+        match robot.find_point_of_interest() {
+            Some(goal) if goal != robot.position => Action::MoveTowards(goal),
+            _ => explore(robot, rng),
+        }
+    }
+}
+
+// la tuile la plus ancienne de la croyance, que les synchronisations tiennent à jour avec la carte de la station
+impl RobotBehavior for PatrolBehavior {
+    fn decide(&self, robot: &Robot, world: &WorldView, rng: &mut dyn RngCore) -> Action {
+        if let Some(action) = follow_task(robot, world) {
+            return action;
+        }
+        match robot.belief.stalest_tile(robot.position) {
+            Some(goal) => Action::ExploreTowards(goal),
+            None => explore(robot, rng),
+        }
+    }
+}
+
+static EXPLORATION: ExplorationBehavior = ExplorationBehavior;
+static COLLECTION: CollectionBehavior = CollectionBehavior;
+static SCIENTIFIC: ScientificBehavior = ScientificBehavior;
+static PATROL: PatrolBehavior = PatrolBehavior;
+
+// associe Behavior::Custom(nom) à une stratégie enregistrée
+// les stratégies ne sont pas sauvegardées dans les snapshots, il faut les réenregistrer après un restore
+#[derive(Clone, Default)]
+pub struct BehaviorRegistry {
+    custom: BTreeMap<String, Rc<dyn RobotBehavior>>,
+}
+
+impl BehaviorRegistry {
+    pub fn register<B: RobotBehavior + 'static>(&mut self, name: &str, behavior: B) {
+        self.custom.insert(name.to_string(), Rc::new(behavior));
+    }
+
+    pub fn resolve(&self, behavior: &Behavior) -> Option<&dyn RobotBehavior> {
+        match behavior {
+            Behavior::Exploration => Some(&EXPLORATION),
+            Behavior::ResourceCollection => Some(&COLLECTION),
+            Behavior::ScientificInterest => Some(&SCIENTIFIC),
+            Behavior::Patrol => Some(&PATROL),
+            Behavior::Custom(name) => self.custom.get(name).map(|b| b.as_ref()),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.custom.keys().map(|name| name.as_str())
+    }
+}
+
+impl fmt::Debug for BehaviorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+// deux registres sont égaux s'ils connaissent les mêmes noms
+impl PartialEq for BehaviorRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.names().eq(other.names())
+    }
+}
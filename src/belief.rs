@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tile::{Resource, TileContent};
+
+// au-delà de ce nombre de ticks sans nouvelle observation, une tuile est considérée périmée
+pub const STALE_AFTER: u64 = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    pub content: TileContent,
+    pub tick: u64,
+    // taille du gisement vue par le robot, 0 si on ne la connaît pas (tuile apprise d'un autre)
+    #[serde(default)]
+    pub quantity: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileState {
+    Unknown,
+    Seen(TileContent),
+    Stale(TileContent),
+}
+
+// la carte telle qu'un robot se la représente : seulement ce qu'il a vu lui-même
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BeliefMap {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Option<Observation>>,
+}
+
+impl BeliefMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![None; width * height],
+        }
+    }
+
+    // un robot créé sans connaître la carte découvre sa taille à la première observation
+    pub fn ensure_size(&mut self, width: usize, height: usize) {
+        if self.width != width || self.height != height {
+            *self = Self::new(width, height);
+        }
+    }
+
+    pub fn check_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn observe(&mut self, x: usize, y: usize, content: TileContent, tick: u64) {
+        self.observe_deposit(x, y, content, 0, tick);
+    }
+
+    pub fn observe_deposit(&mut self, x: usize, y: usize, content: TileContent, quantity: u32, tick: u64) {
+        if self.check_bounds(x, y) {
+            self.tiles[y * self.width + x] = Some(Observation { content, tick, quantity });
+        }
+    }
+
+    pub fn observation(&self, x: usize, y: usize) -> Option<&Observation> {
+        if self.check_bounds(x, y) {
+            self.tiles[y * self.width + x].as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn content_at(&self, x: usize, y: usize) -> Option<TileContent> {
+        self.observation(x, y).map(|observation| observation.content)
+    }
+
+    pub fn state(&self, x: usize, y: usize, tick: u64) -> TileState {
+        match self.observation(x, y) {
+            None => TileState::Unknown,
+            Some(observation) if tick.saturating_sub(observation.tick) > STALE_AFTER => TileState::Stale(observation.content),
+            Some(observation) => TileState::Seen(observation.content),
+        }
+    }
+
+    pub fn known_count(&self) -> usize {
+        self.tiles.iter().filter(|tile| tile.is_some()).count()
+    }
+
+    fn is_passable(&self, x: usize, y: usize) -> bool {
+        self.content_at(x, y).is_some_and(|content| content != TileContent::Obstacle)
+    }
+
+    fn neighbours(&self, (x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        [(0, 1), (1, 0), (0, -1), (-1, 0)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?)))
+            .filter(|&(nx, ny)| self.check_bounds(nx, ny))
+    }
+
+    // une tuile connue et praticable qui touche une tuile jamais vue
+    pub fn is_frontier(&self, x: usize, y: usize) -> bool {
+        self.is_passable(x, y) && self.neighbours((x, y)).any(|(nx, ny)| self.observation(nx, ny).is_none())
+    }
+
+    // la frontière la plus proche en marchant sur les tuiles connues (parcours en largeur), sans compter celle où on est
+    pub fn nearest_frontier(&self, from: (usize, usize)) -> Option<(usize, usize)> {
+        if !self.check_bounds(from.0, from.1) {
+            return None;
+        }
+        let mut visited = vec![false; self.width * self.height];
+        let mut queue = VecDeque::from([from]);
+        visited[from.1 * self.width + from.0] = true;
+        while let Some(position) = queue.pop_front() {
+            if position != from && self.is_frontier(position.0, position.1) {
+                return Some(position);
+            }
+            for (x, y) in self.neighbours(position) {
+                if !visited[y * self.width + x] && self.is_passable(x, y) {
+                    visited[y * self.width + x] = true;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+        None
+    }
+
+    // la tuile praticable observée il y a le plus longtemps, la plus proche en cas d'égalité
+    pub fn stalest_tile(&self, from: (usize, usize)) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), u64, usize)> = None;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            let Some(observation) = tile else {
+                continue;
+            };
+            let position = (index % self.width, index / self.width);
+            if observation.content == TileContent::Obstacle || position == from {
+                continue;
+            }
+            let distance = position.0.abs_diff(from.0) + position.1.abs_diff(from.1);
+            if best.is_none_or(|(_, tick, best_distance)| (observation.tick, distance) < (tick, best_distance)) {
+                best = Some((position, observation.tick, distance));
+            }
+        }
+        best.map(|(position, ..)| position)
+    }
+
+    // la ressource connue la plus proche qui vérifie le filtre
+    pub fn nearest_resource<F: Fn((usize, usize), Resource) -> bool>(&self, from: (usize, usize), filter: F) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), usize)> = None;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if let Some(Observation { content: TileContent::Resource(resource), .. }) = tile {
+                let position = (index % self.width, index / self.width);
+                if filter(position, *resource) {
+                    let distance = position.0.abs_diff(from.0) + position.1.abs_diff(from.1);
+                    if best.is_none_or(|(_, d)| distance < d) {
+                        best = Some((position, distance));
+                    }
+                }
+            }
+        }
+        best.map(|(position, _)| position)
+    }
+
+    // le gisement qui rapporte le plus par case parcourue : un gros gisement loin peut valoir mieux
+    // qu'un petit à côté. On ne compte pas plus d'unités que la place dans la soute, une taille inconnue vaut 1
+    pub fn best_deposit<F: Fn((usize, usize), Resource) -> bool>(&self, from: (usize, usize), space: usize, filter: F) -> Option<(usize, usize)> {
+        let mut best: Option<((usize, usize), usize, usize)> = None;
+        for (index, tile) in self.tiles.iter().enumerate() {
+            if let Some(Observation { content: TileContent::Resource(resource), quantity, .. }) = tile {
+                let position = (index % self.width, index / self.width);
+                if filter(position, *resource) {
+                    let trip = position.0.abs_diff(from.0) + position.1.abs_diff(from.1) + 1;
+                    let units = (*quantity as usize).clamp(1, space.max(1));
+                    // trip / units < best_trip / best_units, sans division
+                    if best.is_none_or(|(_, best_trip, best_units)| trip * best_units < best_trip * units) {
+                        best = Some((position, trip, units));
+                    }
+                }
+            }
+        }
+        best.map(|(position, ..)| position)
+    }
+}
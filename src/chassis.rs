@@ -0,0 +1,221 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::production::BuildCost;
+use crate::robot::{Module, Robot, DEFAULT_CARGO_CAPACITY};
+use crate::sensing::{FieldOfView, BASE_SENSOR_RADIUS};
+use crate::station::Station;
+
+// le châssis standard : 3 emplacements et 10 unités de masse, de quoi porter un module lourd et deux légers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Chassis {
+    pub slots: usize,
+    pub max_mass: u32,
+}
+
+impl Default for Chassis {
+    fn default() -> Self {
+        Self { slots: 3, max_mass: 10 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefitError {
+    // un robot sans module ne sert à rien
+    Empty,
+    NoFreeSlot { slots: usize },
+    TooHeavy { mass: u32, max_mass: u32 },
+    NotInstalled(Module),
+    NotDocked,
+    CannotAfford(BuildCost),
+}
+
+impl fmt::Display for RefitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefitError::Empty => write!(f, "a robot needs at least one module"),
+            RefitError::NoFreeSlot { slots } => write!(f, "the chassis only has {} slots", slots),
+            RefitError::TooHeavy { mass, max_mass } => write!(f, "modules weigh {} but the chassis carries {}", mass, max_mass),
+            RefitError::NotInstalled(module) => write!(f, "no {:?} module to remove", module),
+            RefitError::NotDocked => write!(f, "the robot is not docked at the station"),
+            RefitError::CannotAfford(cost) => write!(f, "the station cannot pay {} energy and {} ore", cost.energy, cost.ore),
+        }
+    }
+}
+
+impl std::error::Error for RefitError {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct RefitStats {
+    pub refitted: u64,
+    // passages à quai où la commande n'a pas pu être appliquée (refusée, ou stocks insuffisants)
+    pub failed: u64,
+}
+
+// ce que la station doit changer sur un robot la prochaine fois qu'il est à quai
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RefitOrder {
+    pub robot_id: usize,
+    pub install: Vec<Module>,
+    pub remove: Vec<Module>,
+}
+
+impl Module {
+    pub fn mass(&self) -> u32 {
+        match self {
+            Module::Analysis => 4,
+            Module::Mining => 5,
+            Module::Imaging => 2,
+            Module::Battery => 3,
+            Module::CargoBay => 3,
+        }
+    }
+
+    // places en plus dans la soute
+    pub fn cargo_bonus(&self) -> usize {
+        match self {
+            Module::CargoBay => DEFAULT_CARGO_CAPACITY,
+            _ => 0,
+        }
+    }
+
+    // unités extraites par tick de forage, les foreuses s'additionnent
+    pub fn mining_rate(&self) -> u32 {
+        match self {
+            Module::Mining => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl Chassis {
+    pub fn check(&self, modules: &[Module]) -> Result<(), RefitError> {
+        if modules.is_empty() {
+            return Err(RefitError::Empty);
+        }
+        if modules.len() > self.slots {
+            return Err(RefitError::NoFreeSlot { slots: self.slots });
+        }
+        let mass = modules.iter().map(Module::mass).sum();
+        if mass > self.max_mass {
+            return Err(RefitError::TooHeavy { mass, max_mass: self.max_mass });
+        }
+        Ok(())
+    }
+}
+
+impl Robot {
+    pub fn has_module(&self, module: Module) -> bool {
+        self.modules.contains(&module)
+    }
+
+    pub fn mass(&self) -> u32 {
+        self.modules.iter().map(Module::mass).sum()
+    }
+
+    pub fn mining_rate(&self) -> u32 {
+        self.modules.iter().map(Module::mining_rate).sum()
+    }
+
+    // le meilleur capteur embarqué, le châssis seul voit ses voisines
+    pub fn field_of_view(&self) -> FieldOfView {
+        self.modules
+            .iter()
+            .map(Module::field_of_view)
+            .max_by_key(|fov| fov.radius)
+            .unwrap_or(FieldOfView { radius: BASE_SENSOR_RADIUS, line_of_sight: false })
+    }
+
+    // la soute et la batterie dépendent des modules, à rappeler après chaque changement
+    pub fn refresh_capabilities(&mut self) {
+        self.cargo_capacity = DEFAULT_CARGO_CAPACITY + self.modules.iter().map(Module::cargo_bonus).sum::<usize>();
+        self.energy = self.energy.min(self.battery_capacity());
+    }
+
+    pub fn install_module(&mut self, module: Module) -> Result<(), RefitError> {
+        let mut modules = self.modules.clone();
+        modules.push(module);
+        self.chassis.check(&modules)?;
+        self.modules = modules;
+        self.refresh_capabilities();
+        Ok(())
+    }
+
+    pub fn remove_module(&mut self, module: Module) -> Result<(), RefitError> {
+        let index = self.modules.iter().position(|installed| *installed == module).ok_or(RefitError::NotInstalled(module))?;
+        let mut modules = self.modules.clone();
+        modules.remove(index);
+        self.chassis.check(&modules)?;
+        self.modules = modules;
+        self.refresh_capabilities();
+        Ok(())
+    }
+}
+
+impl Station {
+    // une seule commande par robot, la dernière remplace la précédente
+    pub fn order_refit(&mut self, order: RefitOrder) {
+        self.refits.retain(|known| known.robot_id != order.robot_id);
+        self.refits.push(order);
+    }
+
+    // applique la commande du robot s'il est à quai. Les modules retirés vont en réserve et sont
+    // réutilisés gratuitement, les autres sont fabriqués au prix d'un robot neuf (sans le temps de montage).
+    // Une commande impossible est abandonnée, une commande trop chère attend que les stocks remontent.
+    // Renvoie vrai si le robot a été modifié
+    pub fn refit(&mut self, robot: &mut Robot) -> Result<bool, RefitError> {
+        let Some(index) = self.refits.iter().position(|order| order.robot_id == robot.id) else {
+            return Ok(false);
+        };
+        if robot.position != self.position {
+            return Err(RefitError::NotDocked);
+        }
+
+        let order = &self.refits[index];
+        let mut modules = robot.modules.clone();
+        let mut spare_modules = self.spare_modules.clone();
+        let checked = order
+            .remove
+            .iter()
+            .try_for_each(|module| {
+                let position = modules.iter().position(|installed| installed == module).ok_or(RefitError::NotInstalled(*module))?;
+                spare_modules.push(modules.remove(position));
+                Ok(())
+            })
+            .and_then(|_| {
+                modules.extend(order.install.iter().copied());
+                robot.chassis.check(&modules)
+            });
+        if let Err(err) = checked {
+            self.refits.remove(index);
+            return Err(err);
+        }
+
+        let mut cost = BuildCost { energy: 0, ore: 0, ticks: 0 };
+        for module in &order.install {
+            match spare_modules.iter().position(|spare| spare == module) {
+                Some(position) => {
+                    spare_modules.remove(position);
+                }
+                None => {
+                    let module_cost = module.build_cost();
+                    cost.energy += module_cost.energy;
+                    cost.ore += module_cost.ore;
+                }
+            }
+        }
+        if !self.can_afford(&cost) {
+            return Err(RefitError::CannotAfford(cost));
+        }
+
+        self.energy -= cost.energy;
+        self.ore -= cost.ore;
+        self.spare_modules = spare_modules;
+        self.refits.remove(index);
+        robot.modules = modules;
+        robot.refresh_capabilities();
+        Ok(true)
+    }
+}
@@ -0,0 +1,232 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::knowledge::KnownTile;
+use crate::robot::{Module, Robot};
+use crate::sync::TileUpdate;
+
+// une réservation de gisement est oubliée au bout de ce nombre de ticks si elle n'est pas renouvelée
+pub const CLAIM_DURATION: u64 = 50;
+// pareil pour un appel de détresse, le robot en panne le répète tant qu'on ne l'a pas secouru
+pub const DISTRESS_DURATION: u64 = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct CommsConfig {
+    // désactivé par défaut : les robots ne passent que par la station
+    pub enabled: bool,
+    pub radius: usize,
+    // probabilité qu'un message n'arrive pas à un destinataire
+    pub packet_loss: f64,
+    // nombre de ticks avant qu'un message arrive dans la boîte du destinataire
+    pub latency: u64,
+}
+
+impl Default for CommsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 5,
+            packet_loss: 0.0,
+            latency: 0,
+        }
+    }
+}
+
+// un robot annonce qu'il part miner ce gisement, les autres vont voir ailleurs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ResourceClaim {
+    pub robot_id: usize,
+    pub position: (usize, usize),
+    pub tick: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DistressCall {
+    pub robot_id: usize,
+    pub position: (usize, usize),
+    pub tick: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Payload {
+    // ce que l'émetteur a vu pendant ce tick
+    MapFragment(Vec<TileUpdate>),
+    ResourceClaim(ResourceClaim),
+    DistressCall(DistressCall),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub from: usize,
+    // None = à tous les robots à portée
+    pub to: Option<usize>,
+    pub sent: u64,
+    pub payload: Payload,
+}
+
+// boîtes aux lettres d'un robot et ce qu'il a appris des autres
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CommsState {
+    pub inbox: Vec<Message>,
+    pub outbox: Vec<Message>,
+    pub claims: Vec<ResourceClaim>,
+    pub own_claim: Option<ResourceClaim>,
+    pub distress_calls: Vec<DistressCall>,
+    // tick du dernier appel envoyé, None quand le robot n'est pas en panne
+    pub distress_sent: Option<u64>,
+}
+
+impl CommsState {
+    // vrai si un autre robot a réservé ce gisement avant nous (les réservations expirées sont retirées par read_inbox)
+    pub fn is_claimed_by_other(&self, position: (usize, usize), robot_id: usize) -> bool {
+        let own = self.own_claim.filter(|claim| claim.position == position);
+        self.claims.iter().any(|claim| {
+            claim.position == position
+                && claim.robot_id != robot_id
+                && own.is_none_or(|own| (claim.tick, claim.robot_id) < (own.tick, own.robot_id))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct CommsStats {
+    pub sent: u64,
+    pub delivered: u64,
+    pub lost: u64,
+    // robots en panne remis en route par un autre
+    pub rescues: u64,
+}
+
+// les messages en route, avec leur destinataire et le tick d'arrivée
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Network {
+    pub in_flight: Vec<(usize, u64, Message)>,
+    pub stats: CommsStats,
+}
+
+fn in_range(a: (usize, usize), b: (usize, usize), radius: usize) -> bool {
+    let dx = a.0.abs_diff(b.0);
+    let dy = a.1.abs_diff(b.1);
+    dx * dx + dy * dy <= radius * radius
+}
+
+impl Robot {
+    pub fn send(&mut self, to: Option<usize>, payload: Payload, tick: u64) {
+        self.comms.outbox.push(Message { from: self.id, to, sent: tick, payload });
+    }
+
+    // ce que le robot a envie de dire aux autres à la fin du tick
+    pub fn compose_messages(&mut self, tick: u64) {
+        let fragment: Vec<_> = self
+            .belief
+            .tiles
+            .iter()
+            .enumerate()
+            .filter_map(|(index, observation)| observation.filter(|observation| observation.tick == tick).map(|observation| (index, observation)))
+            .map(|(index, observation)| TileUpdate {
+                position: (index % self.belief.width, index / self.belief.width),
+                tile: KnownTile { content: observation.content, observer: self.id, tick },
+            })
+            .collect();
+        if !fragment.is_empty() {
+            self.send(None, Payload::MapFragment(fragment), tick);
+        }
+
+        // on renouvelle la réservation avant qu'elle expire chez les autres
+        if self.has_module(Module::Mining) && !self.is_cargo_full() {
+            if let Some(position) = self.find_resource() {
+                let renew = self.comms.own_claim.is_none_or(|claim| claim.position != position || tick.saturating_sub(claim.tick) >= CLAIM_DURATION / 2);
+                if renew {
+                    let claim = ResourceClaim { robot_id: self.id, position, tick };
+                    self.comms.own_claim = Some(claim);
+                    self.send(None, Payload::ResourceClaim(claim), tick);
+                }
+            }
+        }
+
+        if let Some(call) = self.call_for_help(tick) {
+            self.send(None, Payload::DistressCall(call), tick);
+        }
+    }
+
+    // un robot en panne appelle à l'aide, radio ou pas : la station l'entend toujours,
+    // la radio ne sert qu'à relayer l'appel aux autres robots. Renvoie l'appel s'il part à ce tick
+    pub fn call_for_help(&mut self, tick: u64) -> Option<DistressCall> {
+        if !self.stranded {
+            self.comms.distress_sent = None;
+            return None;
+        }
+        if self.comms.distress_sent.is_none_or(|sent| tick.saturating_sub(sent) >= DISTRESS_DURATION / 2) {
+            self.comms.distress_sent = Some(tick);
+        }
+        (self.comms.distress_sent == Some(tick)).then_some(DistressCall { robot_id: self.id, position: self.position, tick })
+    }
+
+    // on vide la boîte de réception au début du tick
+    pub fn read_inbox(&mut self, tick: u64) {
+        for message in std::mem::take(&mut self.comms.inbox) {
+            match message.payload {
+                Payload::MapFragment(tiles) => {
+                    for update in tiles {
+                        self.receive_observation(update.position.0, update.position.1, update.tile.content, update.tile.tick);
+                    }
+                }
+                Payload::ResourceClaim(claim) => {
+                    self.comms.claims.retain(|known| known.robot_id != claim.robot_id);
+                    self.comms.claims.push(claim);
+                }
+                Payload::DistressCall(call) => {
+                    self.comms.distress_calls.retain(|known| known.robot_id != call.robot_id);
+                    self.comms.distress_calls.push(call);
+                }
+            }
+        }
+        self.comms.claims.retain(|claim| tick.saturating_sub(claim.tick) <= CLAIM_DURATION);
+        self.comms.distress_calls.retain(|call| tick.saturating_sub(call.tick) <= DISTRESS_DURATION);
+    }
+}
+
+// le robot a retrouvé de l'énergie : la station prévient tout le monde par radio, les appels en route compris
+pub fn forget_distress(robots: &mut [Robot], network: &mut Network, robot_id: usize) {
+    for robot in robots.iter_mut() {
+        robot.comms.distress_calls.retain(|call| call.robot_id != robot_id);
+        if robot.id == robot_id {
+            robot.comms.distress_sent = None;
+        }
+    }
+    network
+        .in_flight
+        .retain(|(_, _, message)| !matches!(message.payload, Payload::DistressCall(call) if call.robot_id == robot_id));
+}
+
+// envoie les messages des boîtes d'envoi aux robots à portée, puis livre ceux qui sont arrivés
+// la portée est testée au moment de l'envoi, la perte est tirée par destinataire avec la graine de la simulation
+pub fn exchange<R: Rng + ?Sized>(robots: &mut [Robot], network: &mut Network, config: &CommsConfig, rng: &mut R, tick: u64) {
+    let positions: Vec<_> = robots.iter().map(|robot| (robot.id, robot.position)).collect();
+
+    for robot in robots.iter_mut() {
+        for message in std::mem::take(&mut robot.comms.outbox) {
+            network.stats.sent += 1;
+            for &(id, position) in &positions {
+                if id == message.from || message.to.is_some_and(|to| to != id) || !in_range(robot.position, position, config.radius) {
+                    continue;
+                }
+                if config.packet_loss > 0.0 && rng.gen_bool(config.packet_loss.min(1.0)) {
+                    network.stats.lost += 1;
+                    continue;
+                }
+                network.in_flight.push((id, tick + config.latency, message.clone()));
+            }
+        }
+    }
+
+    let (arrived, in_flight): (Vec<_>, Vec<_>) = std::mem::take(&mut network.in_flight).into_iter().partition(|(_, arrival, _)| *arrival <= tick);
+    network.in_flight = in_flight;
+    for (id, _, message) in arrived {
+        if let Some(robot) = robots.iter_mut().find(|robot| robot.id == id) {
+            robot.comms.inbox.push(message);
+            network.stats.delivered += 1;
+        }
+    }
+}
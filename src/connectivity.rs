@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::map::{Map, MapGenConfig};
+use crate::tile::{Resource, TileContent};
+
+// que faire quand les obstacles coupent la carte en plusieurs poches
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Connectivity {
+    // on garde la carte telle quelle (comportement historique)
+    #[default]
+    None,
+    // on creuse des couloirs pour relier chaque poche à la plus grande zone
+    CarveCorridors,
+    // on rejette la carte et on retente avec la graine suivante, puis on creuse si ça ne suffit pas
+    Reseed { max_attempts: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum StationPlacement {
+    // première tuile vide ligne par ligne, comme avant
+    FirstFree,
+    // tuile vide de la plus grande zone la plus proche du centre de la carte
+    CentreMost,
+    // tuile vide la plus centrale de la plus grande zone accessible
+    MaxReachableArea,
+    Fixed((usize, usize)),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConnectivityReport {
+    // graine réellement utilisée (différente de celle demandée si on a retiré la carte)
+    pub seed: u64,
+    pub station: (usize, usize),
+    pub regions: usize,
+    pub carved_tiles: usize,
+    pub reachable_tiles: usize,
+    pub unreachable_resources: Vec<((usize, usize), Resource)>,
+}
+
+fn is_passable(map: &Map, (x, y): (usize, usize)) -> bool {
+    map.tiles[y][x].content != TileContent::Obstacle
+}
+
+fn flood_fill(map: &Map, start: (usize, usize), seen: &mut [bool]) -> Vec<(usize, usize)> {
+    let mut region = vec![];
+    let mut queue = VecDeque::from([start]);
+    seen[start.1 * map.width + start.0] = true;
+
+    while let Some((x, y)) = queue.pop_front() {
+        region.push((x, y));
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        for (dx, dy) in directions {
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                continue;
+            };
+            if map.check_bounds(nx, ny) && !seen[ny * map.width + nx] && is_passable(map, (nx, ny)) {
+                seen[ny * map.width + nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    region
+}
+
+// les zones accessibles de la carte, de la plus grande à la plus petite
+pub fn regions(map: &Map) -> Vec<Vec<(usize, usize)>> {
+    let mut seen = vec![false; map.width * map.height];
+    let mut regions = vec![];
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if !seen[y * map.width + x] && is_passable(map, (x, y)) {
+                regions.push(flood_fill(map, (x, y), &mut seen));
+            }
+        }
+    }
+    regions.sort_by_key(|region| std::cmp::Reverse(region.len()));
+    regions
+}
+
+pub fn reachable_from(map: &Map, start: (usize, usize)) -> Vec<(usize, usize)> {
+    if !map.check_bounds(start.0, start.1) || !is_passable(map, start) {
+        return vec![];
+    }
+    flood_fill(map, start, &mut vec![false; map.width * map.height])
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+// relie chaque poche isolée à la plus grande zone par un couloir en L, renvoie le nombre de tuiles creusées
+pub fn carve_corridors(map: &mut Map) -> usize {
+    let mut carved = 0;
+    loop {
+        let regions = regions(map);
+        if regions.len() <= 1 {
+            return carved;
+        }
+
+        let main = &regions[0];
+        let from = regions[1][0];
+        let to = *main.iter().min_by_key(|&&tile| manhattan(tile, from)).unwrap();
+
+        let (mut x, mut y) = from;
+        while (x, y) != to {
+            if x != to.0 {
+                x = if x < to.0 { x + 1 } else { x - 1 };
+            } else {
+                y = if y < to.1 { y + 1 } else { y - 1 };
+            }
+            if map.tiles[y][x].content == TileContent::Obstacle {
+                map.tiles[y][x].content = TileContent::Empty;
+                carved += 1;
+            }
+        }
+    }
+}
+
+fn centre_most(map: &Map, tiles: &[(usize, usize)], centre: (usize, usize)) -> Option<(usize, usize)> {
+    tiles
+        .iter()
+        .copied()
+        .filter(|&(x, y)| map.is_empty(x, y))
+        .min_by_key(|&(x, y)| (manhattan((x, y), centre), y, x))
+}
+
+pub fn place_station(map: &Map, placement: StationPlacement) -> Option<(usize, usize)> {
+    match placement {
+        StationPlacement::FirstFree => map.find_free_tile(),
+        StationPlacement::CentreMost => {
+            let regions = regions(map);
+            centre_most(map, regions.first()?, (map.width / 2, map.height / 2))
+        }
+        StationPlacement::MaxReachableArea => {
+            let regions = regions(map);
+            let region = regions.first()?;
+            let (sum_x, sum_y) = region.iter().fold((0, 0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+            centre_most(map, region, (sum_x / region.len(), sum_y / region.len()))
+        }
+        StationPlacement::Fixed((x, y)) => map.is_empty(x, y).then_some((x, y)),
+    }
+}
+
+// ce que les robots partis de la station pourront atteindre ou pas
+pub fn analyze(map: &Map, station: (usize, usize)) -> ConnectivityReport {
+    let mut reachable = vec![false; map.width * map.height];
+    let reachable_tiles = reachable_from(map, station);
+    for &(x, y) in &reachable_tiles {
+        reachable[y * map.width + x] = true;
+    }
+
+    let mut unreachable_resources = vec![];
+    for (y, row) in map.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if let TileContent::Resource(resource) = tile.content {
+                if !reachable[y * map.width + x] {
+                    unreachable_resources.push(((x, y), resource));
+                }
+            }
+        }
+    }
+
+    ConnectivityReport {
+        seed: 0,
+        station,
+        regions: regions(map).len(),
+        carved_tiles: 0,
+        reachable_tiles: reachable_tiles.len(),
+        unreachable_resources,
+    }
+}
+
+// génère une carte, applique la règle de connectivité de la config puis place la station
+pub fn generate_world(width: usize, height: usize, seed: u64, config: &MapGenConfig, placement: StationPlacement) -> Option<(Map, ConnectivityReport)> {
+    let mut used_seed = seed;
+    let mut map = Map::with_config(width, height, seed, config);
+    let mut carved_tiles = 0;
+
+    match config.connectivity {
+        Connectivity::None => {}
+        Connectivity::CarveCorridors => carved_tiles = carve_corridors(&mut map),
+        Connectivity::Reseed { max_attempts } => {
+            for attempt in 1..max_attempts {
+                if regions(&map).len() <= 1 {
+                    break;
+                }
+                used_seed = seed.wrapping_add(attempt as u64);
+                map = Map::with_config(width, height, used_seed, config);
+            }
+            carved_tiles = carve_corridors(&mut map);
+        }
+    }
+
+    let station = place_station(&map, placement)?;
+    let mut report = analyze(&map, station);
+    report.seed = used_seed;
+    report.carved_tiles = carved_tiles;
+    Some((map, report))
+}
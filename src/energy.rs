@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::behavior::Action;
+use crate::pathfinding;
+use crate::robot::{Module, Robot};
+
+// ce que coûte chaque chose que fait un robot
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct EnergyModel {
+    pub move_cost: u32,
+    pub mine_cost: u32,
+    // par tick d'analyse
+    pub analyse_cost: u32,
+    // par passage du capteur
+    pub sense_cost: u32,
+    // un tick sans rien faire
+    pub idle_cost: u32,
+    // surcoût d'un déplacement pour chaque tranche de carry_step ressources dans la soute
+    pub carry_cost: u32,
+    pub carry_step: usize,
+    pub return_planner: ReturnPlanner,
+}
+
+// décide quand un robot doit rentrer pour ne pas tomber en panne en route
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ReturnPlanner {
+    pub enabled: bool,
+    // réserve en plus du trajet : un pourcentage du coût du retour puis une quantité fixe
+    pub margin_percent: u32,
+    pub margin: u32,
+}
+
+impl Default for ReturnPlanner {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            margin_percent: 20,
+            margin: 5,
+        }
+    }
+}
+
+// le dernier chemin de retour calculé et les pas faits depuis. Revenir sur ses pas puis suivre ce chemin
+// donne une borne haute du retour, ce qui évite de relancer A* à chaque tick tant qu'on est loin du seuil
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct HomeEstimate {
+    pub steps: Option<usize>,
+    pub walked: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct EnergyStats {
+    // robots tombés en panne loin de la station
+    pub ran_dry: u64,
+    // retours déclenchés par le planificateur
+    pub returns: u64,
+}
+
+impl Default for EnergyModel {
+    fn default() -> Self {
+        Self {
+            move_cost: 1,
+            mine_cost: 2,
+            analyse_cost: 3,
+            sense_cost: 0,
+            idle_cost: 0,
+            carry_cost: 1,
+            carry_step: 3,
+            return_planner: ReturnPlanner::default(),
+        }
+    }
+}
+
+impl EnergyModel {
+    pub fn move_cost_with(&self, cargo: usize) -> u32 {
+        let steps = cargo.checked_div(self.carry_step).unwrap_or(0);
+        self.move_cost + self.carry_cost * steps as u32
+    }
+
+    // un robot qui ne peut plus se payer le moindre pas est en panne, même avec un reste d'énergie
+    pub fn is_stranded(&self, robot: &Robot) -> bool {
+        robot.energy < self.move_cost_with(robot.cargo.len())
+    }
+
+    // ce que coûterait l'action si elle aboutit, pour savoir si le robot peut se la permettre
+    pub fn action_cost(&self, action: &Action, cargo: usize) -> u32 {
+        match action {
+            Action::Idle => self.idle_cost,
+            Action::MoveTowards(_) | Action::MoveRandomly => self.move_cost_with(cargo),
+            Action::ExploreTowards(_) => self.move_cost_with(cargo) + self.sense_cost,
+            Action::Mine => self.mine_cost,
+            Action::Analyze => self.analyse_cost,
+        }
+    }
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+fn steps_to_reach(robot: &Robot, goal: (usize, usize)) -> usize {
+    match robot.route.as_ref().filter(|route| route.is_valid(&robot.belief, robot.position, goal)) {
+        Some(route) => route.remaining().len(),
+        None => pathfinding::path_length(&robot.belief, robot.position, goal).unwrap_or(manhattan(robot.position, goal)),
+    }
+}
+
+impl ReturnPlanner {
+    // énergie pour rentrer à la station par le chemin le plus court que le robot connaît.
+    // Si le robot y va déjà, le reste du chemin en cache suffit ; sans chemin connu, on prend la distance à vol d'oiseau
+    pub fn energy_to_reach(&self, robot: &Robot, goal: (usize, usize), model: &EnergyModel) -> u32 {
+        self.energy_for_steps(robot, steps_to_reach(robot, goal), model)
+    }
+
+    fn energy_for_steps(&self, robot: &Robot, steps: usize, model: &EnergyModel) -> u32 {
+        steps as u32 * (model.move_cost_with(robot.cargo.len()) + model.sense_cost)
+    }
+
+    fn with_margin(&self, trip: u32) -> u32 {
+        trip + trip * self.margin_percent / 100 + self.margin
+    }
+
+    pub fn reserve(&self, robot: &Robot, station: (usize, usize), model: &EnergyModel) -> u32 {
+        self.with_margin(self.energy_to_reach(robot, station, model))
+    }
+
+    // on ne cherche le vrai chemin qu'entre les deux bornes : la ligne droite (plus court possible)
+    // et le dernier chemin calculé allongé des pas faits depuis
+    pub fn should_return(&self, robot: &mut Robot, station: (usize, usize), model: &EnergyModel) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if robot.position == station {
+            robot.home = HomeEstimate { steps: Some(0), walked: 0 };
+            return false;
+        }
+        if robot.energy <= self.with_margin(self.energy_for_steps(robot, manhattan(robot.position, station), model)) {
+            return true;
+        }
+        if let Some(steps) = robot.home.steps {
+            if robot.energy > self.with_margin(self.energy_for_steps(robot, steps + robot.home.walked, model)) {
+                return false;
+            }
+        }
+        let steps = steps_to_reach(robot, station);
+        robot.home = HomeEstimate { steps: Some(steps), walked: 0 };
+        robot.energy <= self.with_margin(self.energy_for_steps(robot, steps, model))
+    }
+}
+
+impl Module {
+    // capacité de la batterie apportée par le module
+    pub fn battery_capacity(&self) -> u32 {
+        match self {
+            Module::Analysis => 100,
+            Module::Mining => 120,
+            Module::Imaging => 80,
+            Module::Battery => 150,
+            Module::CargoBay => 0,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tile::TileContent;
+
+// ce que la station sait d'une tuile, et qui le lui a dit
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct KnownTile {
+    pub content: TileContent,
+    pub observer: usize,
+    pub tick: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeOutcome {
+    // première information sur cette tuile
+    Added,
+    // l'observation reçue remplace celle qu'on avait
+    Replaced,
+    // même contenu, on garde juste la date la plus récente
+    Confirmed,
+    // l'observation reçue est plus ancienne ou moins fiable, on l'ignore
+    Rejected,
+}
+
+// la carte partagée de la station, indexée par coordonnées
+// grille à plat comme la BeliefMap : pas de HashMap à clé (x, y), le JSON ne sait pas les écrire
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct KnowledgeBase {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Option<KnownTile>>,
+    // numéro de la dernière modification, et pour chaque tuile celui de sa dernière mise à jour
+    // permet d'envoyer aux robots seulement ce qui a changé depuis leur dernière synchro
+    pub revision: u64,
+    pub revisions: Vec<u64>,
+}
+
+// à tick égal, un gisement vidé l'emporte sur un gisement plein : une ressource ne réapparaît pas
+// toute seule, c'est donc l'autre robot qui l'a vue juste avant qu'on la mine
+fn confidence(content: TileContent) -> u8 {
+    match content {
+        TileContent::Resource(_) => 0,
+        TileContent::Empty | TileContent::Obstacle => 1,
+    }
+}
+
+impl KnowledgeBase {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![None; width * height],
+            revision: 0,
+            revisions: vec![0; width * height],
+        }
+    }
+
+    // la base prend la taille de la première croyance reçue. Ensuite on ignore les croyances vides
+    // (robot qui n'a encore rien vu) ou d'une autre taille, plutôt que d'effacer ce qu'on sait
+    pub fn accepts(&mut self, width: usize, height: usize) -> bool {
+        if width == 0 || height == 0 {
+            return false;
+        }
+        if self.width == 0 || self.height == 0 {
+            *self = Self::new(width, height);
+        }
+        self.width == width && self.height == height
+    }
+
+    pub fn check_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&KnownTile> {
+        if self.check_bounds(x, y) {
+            self.tiles[y * self.width + x].as_ref()
+        } else {
+            None
+        }
+    }
+
+    // dernier écrivain gagnant (par tick d'observation, pas par heure d'arrivée à la station),
+    // puis la confiance départage deux observations du même tick
+    pub fn merge(&mut self, x: usize, y: usize, incoming: KnownTile) -> MergeOutcome {
+        if !self.check_bounds(x, y) {
+            return MergeOutcome::Rejected;
+        }
+        let index = y * self.width + x;
+        let slot = &mut self.tiles[index];
+        let (outcome, changed) = match slot {
+            None => (MergeOutcome::Added, true),
+            Some(known) if known.content == incoming.content => (MergeOutcome::Confirmed, incoming.tick > known.tick),
+            Some(known) => {
+                let newer = incoming.tick > known.tick;
+                let same_tick_but_surer = incoming.tick == known.tick && confidence(incoming.content) > confidence(known.content);
+                if newer || same_tick_but_surer {
+                    (MergeOutcome::Replaced, true)
+                } else {
+                    (MergeOutcome::Rejected, false)
+                }
+            }
+        };
+        if changed {
+            *slot = Some(incoming);
+            self.revision += 1;
+            self.revisions[index] = self.revision;
+        }
+        outcome
+    }
+
+    // les tuiles modifiées après la révision donnée, de la plus ancienne modification à la plus récente
+    pub fn changes_since(&self, revision: u64) -> Vec<(u64, (usize, usize), KnownTile)> {
+        let mut changes: Vec<_> = self
+            .iter()
+            .map(|((x, y), tile)| (self.revisions[y * self.width + x], (x, y), *tile))
+            .filter(|(tile_revision, ..)| *tile_revision > revision)
+            .collect();
+        changes.sort_by_key(|(tile_revision, ..)| *tile_revision);
+        changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.iter().filter(|tile| tile.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.iter().all(|tile| tile.is_none())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &KnownTile)> {
+        let width = self.width;
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, tile)| tile.as_ref().map(|tile| ((index % width, index / width), tile)))
+    }
+}
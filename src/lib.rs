@@ -1,8 +1,22 @@
 pub mod tile;
 pub mod map;
+pub mod connectivity;
 pub mod robot;
+pub mod chassis;
+pub mod energy;
+pub mod behavior;
+pub mod belief;
+pub mod pathfinding;
+pub mod sensing;
+pub mod science;
+pub mod knowledge;
 pub mod station;
-#[cfg(tests)]
-mod tests {
-    mod map_tests; // Indique que map_tests.rs est dans le sous-dossier tests
-}
\ No newline at end of file
+pub mod sync;
+pub mod comms;
+pub mod tasks;
+pub mod traffic;
+pub mod world_events;
+pub mod production;
+pub mod simulation;
+pub mod snapshot;
+pub mod replay;
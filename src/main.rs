@@ -1,18 +1,22 @@
 use eerea::map::Map;
-use eerea::robot::{Behavior, Module, Robot};
-use eerea::station::{KnownTile, Station};
+use eerea::replay::{Player, Recorder, ReplayError};
+use eerea::simulation::Simulation;
 use eerea::tile::{TileContent, Resource};
 
-use ggez::event::{self};
+use ggez::event::{self, KeyCode, KeyMods};
 use ggez::graphics::{self, Color, DrawParam, Image};
 use ggez::{Context, ContextBuilder, GameResult};
 use std::env;
 use std::path;
 
+// ticks gardés pour le replay, le début de la partie est oublié au-delà
+const REPLAY_TICKS: u64 = 5000;
 
 struct MapMainState {
-    map: Map,
-    station: Station,
+    simulation: Simulation,
+    recorder: Recorder,
+    // Some(..) quand on est en pause et qu'on rejoue la partie image par image
+    player: Option<Player>,
     obstacle_image: Image,
     ore_image: Image,
     energy_image: Image,
@@ -24,7 +28,16 @@ struct MapMainState {
 
 impl MapMainState {
     fn new(ctx: &mut Context) -> GameResult<MapMainState> {
-        let map = Map::new(40, 40, 14);
+        // cargo run -- carte.txt pour jouer un scénario écrit à la main, sinon carte générée
+        let simulation = match env::args().nth(1) {
+            Some(path) => {
+                let (map, station) = Map::load_ascii(&path).map_err(|err| ggez::GameError::ResourceLoadError(err.to_string()))?;
+                let station = station.or_else(|| map.find_free_tile()).expect("Bruh no free tile bro");
+                Simulation::from_map(map, station, 14)
+            }
+            None => Simulation::generate(40, 40, 14).expect("Bruh no free tile bro"),
+        };
+        let recorder = Recorder::bounded(&simulation, REPLAY_TICKS);
         let obstacle_image = Image::new(ctx, "/obstacle.png")?;
         let ore_image = Image::new(ctx, "/ore.png")?;
         let energy_image = Image::new(ctx, "/energy.png")?;
@@ -32,23 +45,11 @@ impl MapMainState {
         let empty_image = Image::new(ctx, "/empty.png")?;
         let robot_image = Image::new(ctx, "/robot.png")?;
         let station_image = Image::new(ctx, "/station.png")?;
-
-        let tile_with_nothing = find_free_tile(&map).expect("Bruh no free tile bro");
-
-        let mut station = Station::new(tile_with_nothing);
-
-        // Ajouter 3 robots avec des rôles différents
-        let robot1 = Robot::new(1, station.position, 100, Module::Analysis, Behavior::Exploration);
-        let robot2 = Robot::new(2, station.position, 100, Module::Mining, Behavior::ResourceCollection);
-        let robot3 = Robot::new(3, station.position, 100, Module::Mining, Behavior::ResourceCollection);
-
-        station.robots.push(robot1);
-        station.robots.push(robot2);
-        station.robots.push(robot3);
         
         let state = MapMainState { 
-            map, 
-            station ,
+            simulation,
+            recorder,
+            player: None,
             obstacle_image, 
             ore_image, 
             energy_image, 
@@ -61,74 +62,57 @@ impl MapMainState {
         Ok(state)
     }
 
-    
-
-    fn update_robots(&mut self) {
-        let mut robots_to_refill = vec![];
-
-        for robot in &mut self.station.robots {
-            if robot.energy == 0 {
-                robot.move_towards_goal(&self.map, self.station.position);
-            } else {
-                robot.perform_action(&mut self.map, self.station.position);
-            }
-            if robot.position == self.station.position {
-                robots_to_refill.push(robot.id);
-            }
+    // l'état affiché : celui du replay en pause, sinon la partie en direct
+    fn shown_simulation(&self) -> &Simulation {
+        match &self.player {
+            Some(player) => player.simulation(),
+            None => &self.simulation,
         }
-
-        self.collect_and_refill_robots(robots_to_refill);
     }
 
-    fn collect_and_refill_robots(&mut self, robots_to_refill: Vec<usize>) {
-        let map = &self.map;
-
-        for robot_id in robots_to_refill {
-            if let Some(robot) = self.station.robots.iter_mut().find(|r| r.id == robot_id) {
-                for &(x, y) in &robot.known_tiles {
-                    if let Some(tile) = map.tile_at(x, y) {
-                        if tile.explored {
-                            let known_tile = self.station.known_tiles.iter_mut().find(|t| t.x == x && t.y == y);
-                            match known_tile {
-                                Some(existing_tile) => {
-                                    if tile.timestamp > existing_tile.timestamp {
-                                        existing_tile.timestamp = tile.timestamp;
-                                    }
-                                }
-                                None => {
-                                    self.station.known_tiles.push(KnownTile { x, y, timestamp: tile.timestamp });
-                                }
-                            }
-                        }
-                    }
-                }
-                robot.refill_energy();
+    fn replay(&mut self, action: impl FnOnce(&mut Player) -> Result<(), ReplayError>) {
+        if let Some(player) = &mut self.player {
+            if let Err(err) = action(player) {
+                println!("Replay error: {}", err);
             }
         }
     }
-
-    fn create_robot_if_needed(&mut self) {
-        if self.station.energy >= 100 {
-            let new_robot = self.station.create_robot(self.station.robots.len() + 1, self.station.position, Module::Imaging, Behavior::Exploration);
-            self.station.energy -= 100;
-            self.station.robots.push(new_robot);
-        }
-    }
 }
 
 impl event::EventHandler<ggez::GameError> for MapMainState {
     fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
-        self.update_robots();
-        self.create_robot_if_needed();
+        if self.player.is_none() {
+            self.recorder.step(&mut self.simulation);
+        }
         Ok(())
     }
 
+    // espace : pause/reprise, gauche/droite : tick précédent/suivant, haut : avance rapide
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
+        match keycode {
+            // les photos prises en enregistrant évitent de tout rejouer depuis le début
+            KeyCode::Space if self.player.is_none() => {
+                self.player = Some(self.recorder.player(self.simulation.behaviors.clone(), self.simulation.build_policy.clone()));
+                let tick = self.simulation.tick;
+                self.replay(|player| player.seek(tick));
+            }
+            KeyCode::Space => self.player = None,
+            KeyCode::Left => self.replay(|player| player.step_backward()),
+            KeyCode::Right => self.replay(|player| player.step_forward().map(|_| ())),
+            KeyCode::Up => self.replay(|player| player.fast_forward(50)),
+            KeyCode::Escape => event::quit(ctx),
+            _ => {}
+        }
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         graphics::clear(ctx, Color::BLACK);
 
+        let simulation = self.shown_simulation();
+
         // carte 
         let tile_size = 32.0;
-        for (y, row) in self.map.tiles.iter().enumerate() {
+        for (y, row) in simulation.map.tiles.iter().enumerate() {
             for (x, tile) in row.iter().enumerate() {
                 let image = match tile.content {
                     TileContent::Obstacle => &self.obstacle_image,
@@ -144,16 +128,24 @@ impl event::EventHandler<ggez::GameError> for MapMainState {
         }
 
         // station
-        let station_draw_params = DrawParam::default().dest([self.station.position.0 as f32 * tile_size, self.station.position.1 as f32 * tile_size]);
+        let station_draw_params = DrawParam::default().dest([simulation.station.position.0 as f32 * tile_size, simulation.station.position.1 as f32 * tile_size]);
         graphics::draw(ctx, &self.station_image, station_draw_params)?;
 
+        let station_info = format!(
+            "Station - Energy: {}, Ore: {}, Science: {}",
+            simulation.station.energy, simulation.station.ore, simulation.station.science_score
+        );
+        let text = graphics::Text::new((station_info, graphics::Font::default(), 20.0));
+        let position = [simulation.station.position.0 as f32 * tile_size, simulation.station.position.1 as f32 * tile_size - 20.0];
+        graphics::draw(ctx, &text, (position, 0.0, graphics::Color::WHITE))?;
+
         //robots
-        for robot in &self.station.robots {
+        for robot in &simulation.station.robots {
             let draw_params = DrawParam::default().dest([robot.position.0 as f32 * tile_size, robot.position.1 as f32 * tile_size]);
             graphics::draw(ctx, &self.robot_image, draw_params)?;
 
             
-            let robot_info = format!("Energy: {}, Module: {:?},", robot.energy, robot.module);
+            let robot_info = format!("Energy: {}, Modules: {:?},", robot.energy, robot.modules);
             let text = graphics::Text::new((robot_info, graphics::Font::default(), 20.0));
             let position = [robot.position.0 as f32 * tile_size, robot.position.1 as f32 * tile_size + 32.0];
             graphics::draw(ctx, &text, (position, 0.0, graphics::Color::WHITE))?;
@@ -165,16 +157,6 @@ impl event::EventHandler<ggez::GameError> for MapMainState {
     
 }
 
-fn find_free_tile(map: &Map) -> Option<(usize, usize)> {
-    for y in 0..map.height {
-        for x in 0..map.width {
-            if let TileContent::Empty = map.tiles[y][x].content {
-                return Some((x, y));
-            }
-        }
-    }
-    None
-}
 fn main() -> GameResult<()> {
     
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
@@ -1,52 +1,241 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
 use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use noise::{NoiseFn, Perlin};
+use crate::connectivity::Connectivity;
 use crate::tile::{Tile, TileContent, Resource};
+use crate::world_events::DustStorm;
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    Empty,
+    RaggedRow { y: usize, expected: usize, found: usize },
+    UnknownChar { ch: char, x: usize, y: usize },
+    MultipleStations,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "map io error: {}", err),
+            MapError::Json(err) => write!(f, "invalid json map: {}", err),
+            MapError::Toml(err) => write!(f, "invalid toml map config: {}", err),
+            MapError::Empty => write!(f, "map is empty"),
+            MapError::RaggedRow { y, expected, found } => {
+                write!(f, "row {} has {} tiles, expected {}", y, found, expected)
+            }
+            MapError::UnknownChar { ch, x, y } => write!(f, "unknown tile '{}' at ({}, {})", ch, x, y),
+            MapError::MultipleStations => write!(f, "map has more than one station"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl From<std::io::Error> for MapError {
+    fn from(err: std::io::Error) -> Self {
+        MapError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(err: serde_json::Error) -> Self {
+        MapError::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for MapError {
+    fn from(err: toml::de::Error) -> Self {
+        MapError::Toml(err)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Map {
     pub width: usize,
     pub height: usize,
     pub tiles: Vec<Vec<Tile>>,
+    // tempêtes de poussière en cours (voir world_events.rs)
+    #[serde(default)]
+    pub storms: Vec<DustStorm>,
+    // les tuiles qui ont porté de l'énergie depuis la création de la carte, elles se rechargent même vidées
+    #[serde(default)]
+    pub energy_sites: BTreeSet<(usize, usize)>,
+}
+
+// tous les réglages de la génération procédurale
+// la valeur par défaut redonne exactement les cartes d'avant la config
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MapGenConfig {
+    // nombre de couches de bruit superposées, 1 = Perlin simple
+    pub octaves: u32,
+    // taille des motifs d'obstacles en tuiles (l'inverse de la fréquence du bruit)
+    pub noise_scale: f64,
+    // au-dessus de ce seuil la tuile est un obstacle : plus il est bas, plus la carte est encombrée
+    pub obstacle_threshold: f64,
+    // chance qu'une tuile libre porte une ressource
+    pub resource_probability: f64,
+    pub resource_weights: ResourceWeights,
+    pub border_thickness: usize,
+    // appliquée par connectivity::generate_world, Map::with_config ne s'en occupe pas
+    pub connectivity: Connectivity,
+    // unités d'énergie ou de minerai par gisement, tirées entre ces deux bornes
+    pub min_deposit: u32,
+    pub max_deposit: u32,
+}
+
+// poids relatifs des ressources générées
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ResourceWeights {
+    pub energy: u32,
+    pub ore: u32,
+    pub place_of_interest: u32,
+}
+
+impl Default for MapGenConfig {
+    fn default() -> Self {
+        Self {
+            octaves: 1,
+            noise_scale: 5.0,
+            obstacle_threshold: 0.5,
+            resource_probability: 0.3,
+            resource_weights: ResourceWeights::default(),
+            border_thickness: 1,
+            connectivity: Connectivity::None,
+            min_deposit: 1,
+            max_deposit: 4,
+        }
+    }
+}
+
+impl Default for ResourceWeights {
+    fn default() -> Self {
+        Self {
+            energy: 1,
+            ore: 1,
+            place_of_interest: 1,
+        }
+    }
+}
+
+impl ResourceWeights {
+    // avec des poids 1/1/1 on tire exactement comme Resource::random_resource
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Resource {
+        let total = self.energy + self.ore + self.place_of_interest;
+        if total == 0 {
+            return Resource::random_resource(rng);
+        }
+        let roll = rng.gen_range(0..total);
+        if roll < self.energy {
+            Resource::Energy
+        } else if roll < self.energy + self.ore {
+            Resource::Ore
+        } else {
+            Resource::PlaceOfInterest
+        }
+    }
+}
+
+impl MapGenConfig {
+    // bruit fractal : chaque octave double la fréquence et divise l'amplitude par deux
+    fn noise_at(&self, perlin: &Perlin, x: usize, y: usize) -> f64 {
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut norm = 0.0;
+        for _ in 0..self.octaves.max(1) {
+            total += perlin.get([x as f64 / self.noise_scale * frequency, y as f64 / self.noise_scale * frequency]) * amplitude;
+            norm += amplitude;
+            frequency *= 2.0;
+            amplitude *= 0.5;
+        }
+        total / norm
+    }
+
+    pub fn deposit_quantity<R: Rng>(&self, rng: &mut R) -> u32 {
+        let min = self.min_deposit.max(1);
+        rng.gen_range(min..=self.max_deposit.max(min))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MapError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, MapError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    // le format est choisi d'après l'extension du fichier (.toml ou .json)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MapError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            _ => Self::from_json(&text),
+        }
+    }
 }
 
 impl Map {
     pub fn new(width: usize, height: usize, seed: u64) -> Self {
-        let tiles = Self::generate_tiles(width, height, seed);
-        Self {
+        Self::with_config(width, height, seed, &MapGenConfig::default())
+    }
+
+    pub fn with_config(width: usize, height: usize, seed: u64, config: &MapGenConfig) -> Self {
+        let tiles = Self::generate_tiles_with(width, height, seed, config);
+        let mut map = Self {
             width,
             height,
             tiles,
-        }
+            storms: vec![],
+            energy_sites: BTreeSet::new(),
+        };
+        map.record_energy_sites();
+        map
     }
 
     pub fn generate_tiles(width: usize, height: usize, seed: u64) -> Vec<Vec<Tile>> {
+        Self::generate_tiles_with(width, height, seed, &MapGenConfig::default())
+    }
+
+    pub fn generate_tiles_with(width: usize, height: usize, seed: u64, config: &MapGenConfig) -> Vec<Vec<Tile>> {
         let mut rand = rand::rngs::StdRng::seed_from_u64(seed);
+        // les quantités ont leur propre tirage pour ne pas changer la disposition des cartes existantes
+        let mut amounts = rand::rngs::StdRng::seed_from_u64(seed ^ 0xDE905172);
         let mut tiles = vec![vec![Tile::new(false, TileContent::Empty); width]; height];
 
-        let perlin = Perlin::new(10);
+        // le bruit suit la graine de la carte, sinon deux graines donnent les mêmes obstacles.
+        // Perlin ne prend que 32 bits : on replie la moitié haute sur la basse plutôt que de la perdre
+        let perlin = Perlin::new((seed ^ (seed >> 32)) as u32);
+        let border = config.border_thickness;
 
-        // Bordures comme obstacles
-        for x in 0..width {
-            tiles[0][x] = Tile::new(false, TileContent::Obstacle);
-            tiles[height - 1][x] = Tile::new(false, TileContent::Obstacle);
-        }
-
-        for y in 0..height {
-            tiles[y][0] = Tile::new(false, TileContent::Obstacle);
-            tiles[y][width - 1] = Tile::new(false, TileContent::Obstacle);
-        }
+        for (y, row) in tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                // Bordures comme obstacles
+                if x < border || y < border || x + border >= width || y + border >= height {
+                    *tile = Tile::new(false, TileContent::Obstacle);
+                    continue;
+                }
 
-        for y in 1..height - 1 {
-            for x in 1..width - 1 {
-                let perlin_noise = perlin.get([x as f64 / 5.0, y as f64 / 5.0]); 
-                if perlin_noise > 0.5 {
-                    tiles[y][x] = Tile::new(false, TileContent::Obstacle);
+                if config.noise_at(&perlin, x, y) > config.obstacle_threshold {
+                    *tile = Tile::new(false, TileContent::Obstacle);
                 } else {
                     let resource_probability: f64 = rand.gen();
-                    if resource_probability < 0.3 {
-                        let resource = Resource::random_resource(&mut rand);
-                        tiles[y][x] = Tile::new(false, TileContent::Resource(resource));
+                    if resource_probability < config.resource_probability {
+                        let resource = config.resource_weights.pick(&mut rand);
+                        *tile = match resource {
+                            Resource::PlaceOfInterest => Tile::new(false, TileContent::Resource(resource)),
+                            _ => Tile::deposit(resource, config.deposit_quantity(&mut amounts)),
+                        };
                     }
                 }
             }
@@ -55,10 +244,111 @@ impl Map {
         tiles
     }
 
+    // lit une carte texte, une ligne par rangée, avec la légende :
+    //   # obstacle   E énergie   O minerai   P lieu d'intérêt   S station   . vide
+    // la station est posée sur une tuile vide, la carte ne la stocke pas : on renvoie sa position à part
+    pub fn from_ascii(text: &str) -> Result<(Self, Option<(usize, usize)>), MapError> {
+        let rows: Vec<&str> = text.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()).collect();
+        let width = rows.first().ok_or(MapError::Empty)?.chars().count();
+        let mut station = None;
+        let mut tiles = Vec::with_capacity(rows.len());
+
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != width {
+                return Err(MapError::RaggedRow { y, expected: width, found });
+            }
+            let mut tile_row = Vec::with_capacity(width);
+            for (x, ch) in row.chars().enumerate() {
+                let content = match ch {
+                    '#' => TileContent::Obstacle,
+                    'E' => TileContent::Resource(Resource::Energy),
+                    'O' => TileContent::Resource(Resource::Ore),
+                    'P' => TileContent::Resource(Resource::PlaceOfInterest),
+                    '.' => TileContent::Empty,
+                    'S' => {
+                        if station.replace((x, y)).is_some() {
+                            return Err(MapError::MultipleStations);
+                        }
+                        TileContent::Empty
+                    }
+                    _ => return Err(MapError::UnknownChar { ch, x, y }),
+                };
+                tile_row.push(Tile::new(false, content));
+            }
+            tiles.push(tile_row);
+        }
+
+        let mut map = Self {
+            width,
+            height: tiles.len(),
+            tiles,
+            storms: vec![],
+            energy_sites: BTreeSet::new(),
+        };
+        map.record_energy_sites();
+        Ok((map, station))
+    }
+
+    pub fn load_ascii<P: AsRef<Path>>(path: P) -> Result<(Self, Option<(usize, usize)>), MapError> {
+        Self::from_ascii(&fs::read_to_string(path)?)
+    }
+
+    // l'inverse de from_ascii, une ligne par rangée de tuiles
+    pub fn to_ascii(&self, station: Option<(usize, usize)>) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let ch = match tile.content {
+                    _ if station == Some((x, y)) => 'S',
+                    TileContent::Obstacle => '#',
+                    TileContent::Resource(Resource::Energy) => 'E',
+                    TileContent::Resource(Resource::Ore) => 'O',
+                    TileContent::Resource(Resource::PlaceOfInterest) => 'P',
+                    TileContent::Empty => '.',
+                };
+                text.push(ch);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // le format json est la carte sérialisée telle quelle (tuiles explorées et timestamps compris)
+    pub fn from_json(json: &str) -> Result<Self, MapError> {
+        let mut map: Map = serde_json::from_str(json)?;
+        if map.tiles.is_empty() {
+            return Err(MapError::Empty);
+        }
+        if map.tiles.len() != map.height {
+            return Err(MapError::RaggedRow { y: map.tiles.len(), expected: map.height, found: map.tiles.len() });
+        }
+        for (y, row) in map.tiles.iter().enumerate() {
+            if row.len() != map.width {
+                return Err(MapError::RaggedRow { y, expected: map.width, found: row.len() });
+            }
+        }
+        // les cartes d'avant la liste : on garde au moins l'énergie encore présente
+        map.record_energy_sites();
+        Ok(map)
+    }
+
+    pub fn to_json(&self) -> Result<String, MapError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, MapError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
     pub fn check_bounds(&self, x: usize, y: usize) -> bool {
         x < self.width && y < self.height
     }
 
+    pub fn in_storm(&self, position: (usize, usize)) -> bool {
+        self.storms.iter().any(|storm| storm.covers(position))
+    }
+
     pub fn is_empty(&self, x: usize, y: usize) -> bool {
         if !self.check_bounds(x, y) {
             return false; 
@@ -67,8 +357,34 @@ impl Map {
     }
 
     pub fn throw_resource_at(&mut self, x: usize, y: usize, resource: Resource) {
+        self.throw_deposit_at(x, y, resource, 1);
+    }
+
+    pub fn throw_deposit_at(&mut self, x: usize, y: usize, resource: Resource, quantity: u32) {
         if let Some(tile) = self.tile_at_mut(x, y) {
             tile.content = TileContent::Resource(resource);
+            tile.quantity = quantity.max(1);
+            if resource == Resource::Energy {
+                self.energy_sites.insert((x, y));
+            }
+        }
+    }
+
+    fn record_energy_sites(&mut self) {
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if tile.content == TileContent::Resource(Resource::Energy) {
+                    self.energy_sites.insert((x, y));
+                }
+            }
+        }
+    }
+
+    // la ressource de la tuile et ce qu'il en reste
+    pub fn deposit_at(&self, x: usize, y: usize) -> Option<(Resource, u32)> {
+        match self.tile_at(x, y)? {
+            Tile { content: TileContent::Resource(resource), quantity, .. } => Some((*resource, *quantity)),
+            _ => None,
         }
     }
 
@@ -88,10 +404,26 @@ impl Map {
         }
     }
 
+    // première tuile vide en parcourant ligne par ligne, pour poser la station
+    pub fn find_free_tile(&self) -> Option<(usize, usize)> {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let TileContent::Empty = self.tiles[y][x].content {
+                    return Some((x, y));
+                }
+            }
+        }
+        None
+    }
+
+    // extrait une unité, le gisement s'épuise petit à petit
     pub fn retrieve_resource_at(&mut self, x: usize, y: usize) -> Option<Resource> {
         if let Some(tile) = self.tile_at_mut(x, y) {
             if let TileContent::Resource(resource) = tile.content {
-                tile.content = TileContent::Empty;
+                tile.quantity = tile.quantity.saturating_sub(1);
+                if tile.quantity == 0 {
+                    tile.content = TileContent::Empty;
+                }
                 Some(resource)
             } else {
                 None
@@ -0,0 +1,173 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::belief::BeliefMap;
+use crate::map::Map;
+use crate::tile::TileContent;
+
+// coût pour entrer sur une tuile, None si on ne peut pas y aller
+// traverser un gisement est plus lent que traverser une case vide
+pub fn terrain_cost(content: TileContent) -> Option<u32> {
+    match content {
+        TileContent::Empty => Some(1),
+        TileContent::Resource(_) => Some(2),
+        TileContent::Obstacle => None,
+    }
+}
+
+// tout ce sur quoi on peut chercher un chemin : la vraie carte ou ce qu'un robot en sait
+pub trait CostMap {
+    fn dimensions(&self) -> (usize, usize);
+    fn cost_at(&self, position: (usize, usize)) -> Option<u32>;
+}
+
+impl CostMap for Map {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn cost_at(&self, (x, y): (usize, usize)) -> Option<u32> {
+        self.tile_at(x, y).and_then(|tile| terrain_cost(tile.content))
+    }
+}
+
+// les tuiles inconnues sont supposées praticables, le robot verra bien en s'approchant
+impl CostMap for BeliefMap {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn cost_at(&self, (x, y): (usize, usize)) -> Option<u32> {
+        if !self.check_bounds(x, y) {
+            return None;
+        }
+        match self.content_at(x, y) {
+            Some(content) => terrain_cost(content),
+            None => Some(1),
+        }
+    }
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32
+}
+
+fn neighbours((width, height): (usize, usize), (x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+    directions.into_iter().filter_map(move |(dx, dy)| {
+        let nx = x.checked_add_signed(dx)?;
+        let ny = y.checked_add_signed(dy)?;
+        (nx < width && ny < height).then_some((nx, ny))
+    })
+}
+
+// un chemin calculé : les pas (sans la case de départ) et le coût de chacun au moment du calcul
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Route {
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    pub steps: Vec<(usize, usize)>,
+    pub costs: Vec<u32>,
+    pub next: usize,
+}
+
+impl Route {
+    // la case où le robot doit se trouver s'il a bien suivi la route
+    pub fn expected_position(&self) -> (usize, usize) {
+        match self.next {
+            0 => self.start,
+            next => self.steps[next - 1],
+        }
+    }
+
+    pub fn remaining_cost(&self) -> u32 {
+        self.costs[self.next..].iter().sum()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+
+    // la route reste bonne tant que le robot est dessus et que la carte n'a pas changé sur le reste du trajet
+    pub fn is_valid<M: CostMap>(&self, map: &M, position: (usize, usize), goal: (usize, usize)) -> bool {
+        self.goal == goal
+            && self.expected_position() == position
+            && self.steps[self.next..]
+                .iter()
+                .zip(&self.costs[self.next..])
+                .all(|(&step, &cost)| map.cost_at(step) == Some(cost))
+    }
+
+    // les pas qui restent à faire
+    pub fn remaining(&self) -> &[(usize, usize)] {
+        &self.steps[self.next.min(self.steps.len())..]
+    }
+
+    pub fn peek(&self) -> Option<(usize, usize)> {
+        self.steps.get(self.next).copied()
+    }
+
+    pub fn advance(&mut self) -> Option<(usize, usize)> {
+        let step = *self.steps.get(self.next)?;
+        self.next += 1;
+        Some(step)
+    }
+}
+
+// A* avec l'heuristique de Manhattan (admissible, une case coûte au moins 1)
+pub fn find_path<M: CostMap>(map: &M, start: (usize, usize), goal: (usize, usize)) -> Option<Route> {
+    let (width, height) = map.dimensions();
+    if start.0 >= width || start.1 >= height || (map.cost_at(goal).is_none() && start != goal) {
+        return None;
+    }
+
+    let index = |(x, y): (usize, usize)| y * width + x;
+    let mut g_score = vec![u32::MAX; width * height];
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; width * height];
+    let mut open = BinaryHeap::new();
+
+    g_score[index(start)] = 0;
+    open.push(Reverse((manhattan(start, goal), 0, start)));
+
+    while let Some(Reverse((_, g, current))) = open.pop() {
+        if current == goal {
+            let mut steps = vec![];
+            let mut node = current;
+            while node != start {
+                steps.push(node);
+                node = came_from[index(node)]?;
+            }
+            steps.reverse();
+            let costs = steps.iter().map(|&step| map.cost_at(step).unwrap_or(0)).collect();
+            return Some(Route { start, goal, steps, costs, next: 0 });
+        }
+        if g > g_score[index(current)] {
+            continue;
+        }
+
+        for next in neighbours((width, height), current) {
+            if let Some(cost) = map.cost_at(next) {
+                let tentative = g + cost;
+                if tentative < g_score[index(next)] {
+                    g_score[index(next)] = tentative;
+                    came_from[index(next)] = Some(current);
+                    open.push(Reverse((tentative + manhattan(next, goal), tentative, next)));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// coût total d'un trajet, pour les décisions qui ont besoin d'une distance et pas juste du prochain pas
+pub fn path_cost<M: CostMap>(map: &M, start: (usize, usize), goal: (usize, usize)) -> Option<u32> {
+    find_path(map, start, goal).map(|route| route.remaining_cost())
+}
+
+// nombre de pas d'un trajet, pour ce qui se paie au déplacement (l'énergie) plutôt qu'au terrain
+pub fn path_length<M: CostMap>(map: &M, start: (usize, usize), goal: (usize, usize)) -> Option<usize> {
+    find_path(map, start, goal).map(|route| route.steps.len())
+}
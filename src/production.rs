@@ -0,0 +1,222 @@
+use std::fmt;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::robot::{Behavior, Module};
+use crate::station::Station;
+use crate::tasks::TaskKind;
+use crate::tile::{Resource, TileContent};
+
+// au bout de combien de ticks sans pouvoir payer on redemande à la politique ce qu'il faut construire
+pub const REPLAN_AFTER: u32 = 50;
+
+// ce que coûte la fabrication d'un robot équipé de ce module
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BuildCost {
+    pub energy: u32,
+    pub ore: u32,
+    pub ticks: u32,
+}
+
+impl Module {
+    pub fn build_cost(&self) -> BuildCost {
+        match self {
+            Module::Analysis => BuildCost { energy: 100, ore: 4, ticks: 30 },
+            Module::Mining => BuildCost { energy: 75, ore: 3, ticks: 20 },
+            Module::Imaging => BuildCost { energy: 50, ore: 2, ticks: 15 },
+            Module::Battery => BuildCost { energy: 40, ore: 2, ticks: 10 },
+            Module::CargoBay => BuildCost { energy: 30, ore: 3, ticks: 10 },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BuildOrder {
+    pub module: Module,
+    pub behavior: Behavior,
+}
+
+// le robot en cours de montage, déjà payé
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Production {
+    pub order: BuildOrder,
+    pub ticks_left: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ProductionQueue {
+    // commandes en attente, la première qu'on peut payer est lancée
+    pub queue: Vec<BuildOrder>,
+    pub current: Option<Production>,
+    pub built: u64,
+    // ticks passés sans pouvoir payer aucune commande de la file
+    pub blocked: u32,
+}
+
+// décide quoi construire quand la file est vide, None pour ne rien construire
+pub trait BuildPolicy {
+    fn next_build(&self, station: &Station) -> Option<BuildOrder>;
+
+    // sert à comparer deux simulations, le nom du type par défaut
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+// construit ce qui manque à l'essaim : des mineurs d'abord, des éclaireurs tant que la carte
+// est mal connue, des scientifiques s'il reste des points d'intérêt, puis des mineurs si le travail s'accumule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwarmNeedsPolicy {
+    pub max_robots: usize,
+    pub min_miners: usize,
+    // part de la carte que la station doit connaître avant d'arrêter les éclaireurs
+    pub explored_target: f64,
+    pub max_scouts: usize,
+}
+
+impl Default for SwarmNeedsPolicy {
+    fn default() -> Self {
+        Self {
+            max_robots: 12,
+            min_miners: 2,
+            explored_target: 0.8,
+            max_scouts: 3,
+        }
+    }
+}
+
+impl BuildPolicy for SwarmNeedsPolicy {
+    fn next_build(&self, station: &Station) -> Option<BuildOrder> {
+        if station.robots.len() >= self.max_robots {
+            return None;
+        }
+        let count = |module: Module| station.robots.iter().filter(|robot| robot.has_module(module)).count();
+        let miners = count(Module::Mining);
+        let order = |module, behavior| Some(BuildOrder { module, behavior });
+
+        if miners < self.min_miners {
+            return order(Module::Mining, Behavior::ResourceCollection);
+        }
+
+        let knowledge = &station.known_tiles;
+        let area = knowledge.width * knowledge.height;
+        let explored = if area == 0 { 0.0 } else { knowledge.len() as f64 / area as f64 };
+        if explored < self.explored_target && count(Module::Imaging) < self.max_scouts {
+            return order(Module::Imaging, Behavior::Exploration);
+        }
+
+        let analysed = |position: (usize, usize)| station.science_reports.iter().any(|report| report.location == position);
+        let open_points_of_interest = knowledge
+            .iter()
+            .filter(|(position, tile)| tile.content == TileContent::Resource(Resource::PlaceOfInterest) && !analysed(*position))
+            .count();
+        if open_points_of_interest > 2 * count(Module::Analysis) {
+            return order(Module::Analysis, Behavior::ScientificInterest);
+        }
+
+        let waiting_deposits = station
+            .task_board
+            .unassigned()
+            .filter(|task| matches!(task.kind, TaskKind::MineAt(_)))
+            .count();
+        if waiting_deposits > miners {
+            return order(Module::Mining, Behavior::ResourceCollection);
+        }
+        None
+    }
+}
+
+// toujours le même robot, pour les scénarios de test
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedPolicy(pub BuildOrder);
+
+impl BuildPolicy for FixedPolicy {
+    fn next_build(&self, _station: &Station) -> Option<BuildOrder> {
+        Some(self.0.clone())
+    }
+}
+
+// la politique de la simulation, pas sauvegardée dans les snapshots comme les comportements
+#[derive(Clone)]
+pub struct PolicyHandle(pub Rc<dyn BuildPolicy>);
+
+impl PolicyHandle {
+    pub fn new<P: BuildPolicy + 'static>(policy: P) -> Self {
+        Self(Rc::new(policy))
+    }
+}
+
+impl Default for PolicyHandle {
+    fn default() -> Self {
+        Self::new(SwarmNeedsPolicy::default())
+    }
+}
+
+impl fmt::Debug for PolicyHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+// deux politiques sont égales si elles portent le même nom
+impl PartialEq for PolicyHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Station {
+    pub fn can_afford(&self, cost: &BuildCost) -> bool {
+        self.energy >= cost.energy && self.ore >= cost.ore
+    }
+
+    // un tick de l'atelier : on remplit la file, on lance la première commande qu'on peut payer,
+    // on avance le montage en cours. Renvoie l'id du robot terminé à ce tick.
+    // Si rien n'est payable pendant REPLAN_AFTER ticks, la politique revoit la commande en tête :
+    // les besoins de l'essaim ont pu changer depuis
+    pub fn update_production(&mut self, policy: &dyn BuildPolicy) -> Option<usize> {
+        if self.production.queue.is_empty() && self.production.current.is_none() {
+            if let Some(order) = policy.next_build(self) {
+                self.production.queue.push(order);
+            }
+        }
+
+        if self.production.current.is_none() {
+            let affordable = self.production.queue.iter().position(|order| self.can_afford(&order.module.build_cost()));
+            if let Some(index) = affordable {
+                let order = self.production.queue.remove(index);
+                let cost = order.module.build_cost();
+                self.energy -= cost.energy;
+                self.ore -= cost.ore;
+                self.production.current = Some(Production { order, ticks_left: cost.ticks });
+                self.production.blocked = 0;
+            } else if self.production.queue.is_empty() {
+                self.production.blocked = 0;
+            } else {
+                self.production.blocked += 1;
+                if self.production.blocked >= REPLAN_AFTER {
+                    self.production.blocked = 0;
+                    match policy.next_build(self) {
+                        Some(order) => self.production.queue[0] = order,
+                        None => {
+                            self.production.queue.remove(0);
+                        }
+                    }
+                }
+            }
+        }
+
+        let production = self.production.current.as_mut()?;
+        production.ticks_left = production.ticks_left.saturating_sub(1);
+        if production.ticks_left > 0 {
+            return None;
+        }
+        let BuildOrder { module, behavior } = self.production.current.take()?.order;
+        let id = self.robots.iter().map(|robot| robot.id).max().unwrap_or(0) + 1;
+        self.create_robot(id, self.position, module, behavior);
+        self.production.built += 1;
+        Some(id)
+    }
+}
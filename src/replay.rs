@@ -0,0 +1,334 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::behavior::BehaviorRegistry;
+use crate::production::PolicyHandle;
+use crate::robot::Module;
+use crate::simulation::Simulation;
+use crate::snapshot::{Snapshot, SnapshotError, SNAPSHOT_VERSION};
+use crate::tile::{Resource, TileContent};
+
+// toutes les KEYFRAME_INTERVAL ticks le lecteur garde une photo, pour reculer sans tout rejouer
+const KEYFRAME_INTERVAL: u64 = 100;
+
+// tout ce qui peut changer l'état du monde pendant un tick
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    RobotMoved { robot_id: usize, from: (usize, usize), to: (usize, usize) },
+    ResourceRetrieved { position: (usize, usize), resource: Resource },
+    TileExplored { position: (usize, usize), tick: u64 },
+    TileChanged { position: (usize, usize), content: TileContent },
+    // le gisement a perdu ou gagné des unités sans changer de nature
+    DepositChanged { position: (usize, usize), quantity: u32 },
+    RobotRecharged { robot_id: usize, energy: u32 },
+    CargoUnloaded { robot_id: usize, cargo: Vec<Resource> },
+    RobotCreated { robot_id: usize, position: (usize, usize), modules: Vec<Module> },
+    RobotRefitted { robot_id: usize, modules: Vec<Module> },
+    ReportsDelivered { robot_id: usize, report_ids: Vec<u64> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TickEvents {
+    pub tick: u64,
+    pub events: Vec<Event>,
+}
+
+// la photo de départ + les évènements de chaque tick
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayLog {
+    pub initial: Snapshot,
+    pub ticks: Vec<TickEvents>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    // la simulation rejouée ne produit plus les évènements enregistrés
+    Desync { tick: u64 },
+    OutOfRange { tick: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Desync { tick } => write!(f, "replay diverged from the log at tick {}", tick),
+            ReplayError::OutOfRange { tick } => write!(f, "tick {} is outside of the replay", tick),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl ReplayLog {
+    pub fn start_tick(&self) -> u64 {
+        self.initial.simulation.tick
+    }
+
+    pub fn end_tick(&self) -> u64 {
+        self.start_tick() + self.ticks.len() as u64
+    }
+
+    pub fn events_at(&self, tick: u64) -> Option<&[Event]> {
+        let index = tick.checked_sub(self.start_tick())? as usize;
+        self.ticks.get(index).map(|t| t.events.as_slice())
+    }
+
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        let log: ReplayLog = serde_json::from_str(json)?;
+        if log.initial.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(log.initial.version));
+        }
+        Ok(log)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+// avance la simulation d'un tick et renvoie ce qui a changé
+// on compare l'état avant/après, comme ça aucun code de la simulation n'a besoin de connaître le replay
+pub fn step_with_events(simulation: &mut Simulation) -> Vec<Event> {
+    // pas besoin de cloner les robots entiers (carte de croyance comprise), juste ce qu'on compare
+    let robots_before: Vec<_> = simulation
+        .station
+        .robots
+        .iter()
+        .map(|r| (r.id, r.position, r.energy, r.cargo.clone(), r.reports.iter().map(|report| report.id).collect::<Vec<_>>(), r.modules.clone()))
+        .collect();
+    let tiles_before = simulation.map.tiles.clone();
+
+    simulation.step();
+
+    let mut events = vec![];
+
+    for robot in &simulation.station.robots {
+        match robots_before.iter().find(|(id, ..)| *id == robot.id) {
+            Some((_, position, energy, cargo, reports, modules)) => {
+                if *position != robot.position {
+                    events.push(Event::RobotMoved { robot_id: robot.id, from: *position, to: robot.position });
+                }
+                if robot.energy > *energy {
+                    events.push(Event::RobotRecharged { robot_id: robot.id, energy: robot.energy });
+                }
+                if robot.cargo.is_empty() && !cargo.is_empty() {
+                    events.push(Event::CargoUnloaded { robot_id: robot.id, cargo: cargo.clone() });
+                }
+                if robot.reports.is_empty() && !reports.is_empty() {
+                    events.push(Event::ReportsDelivered { robot_id: robot.id, report_ids: reports.clone() });
+                }
+                if robot.modules != *modules {
+                    events.push(Event::RobotRefitted { robot_id: robot.id, modules: robot.modules.clone() });
+                }
+            }
+            None => events.push(Event::RobotCreated { robot_id: robot.id, position: robot.position, modules: robot.modules.clone() }),
+        }
+    }
+
+    for (y, row) in simulation.map.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let before = &tiles_before[y][x];
+            if before.content != tile.content {
+                match before.content {
+                    TileContent::Resource(resource) if tile.content == TileContent::Empty => {
+                        events.push(Event::ResourceRetrieved { position: (x, y), resource });
+                    }
+                    _ => events.push(Event::TileChanged { position: (x, y), content: tile.content }),
+                }
+            } else if before.quantity != tile.quantity && matches!(tile.content, TileContent::Resource(_)) {
+                events.push(Event::DepositChanged { position: (x, y), quantity: tile.quantity });
+            }
+            // seulement la première fois, sinon chaque robot génère un évènement par tuile vue à chaque tick
+            if tile.explored && !before.explored {
+                events.push(Event::TileExplored { position: (x, y), tick: tile.timestamp });
+            }
+        }
+    }
+
+    events
+}
+
+// enregistre une partie en cours. Le log ne sert qu'à rejouer et à vérifier que la relecture ne diverge pas,
+// jamais à reconstruire la partie en direct : on peut donc en oublier le début (voir `bounded`)
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    log: ReplayLog,
+    // les photos prises en enregistrant, toutes les KEYFRAME_INTERVAL ticks depuis le début du log
+    keyframes: Vec<Snapshot>,
+    // nombre de ticks gardés au plus, None pour tout garder
+    max_ticks: Option<u64>,
+}
+
+impl Recorder {
+    pub fn new(simulation: &Simulation) -> Self {
+        let initial = simulation.snapshot();
+        Self {
+            keyframes: vec![initial.clone()],
+            log: ReplayLog { initial, ticks: vec![] },
+            max_ticks: None,
+        }
+    }
+
+    // garde au moins les max_ticks derniers ticks : le début du log avance d'une photo à la fois
+    pub fn bounded(simulation: &Simulation, max_ticks: u64) -> Self {
+        Self {
+            max_ticks: Some(max_ticks.max(KEYFRAME_INTERVAL)),
+            ..Self::new(simulation)
+        }
+    }
+
+    pub fn step(&mut self, simulation: &mut Simulation) -> &[Event] {
+        let tick = simulation.tick;
+        let events = step_with_events(simulation);
+        self.log.ticks.push(TickEvents { tick, events });
+        if (self.log.ticks.len() as u64).is_multiple_of(KEYFRAME_INTERVAL) {
+            self.keyframes.push(simulation.snapshot());
+        }
+        self.trim();
+        &self.log.ticks.last().unwrap().events
+    }
+
+    // le log repart de la deuxième photo dès que le suivant suffit à couvrir max_ticks
+    fn trim(&mut self) {
+        let Some(max_ticks) = self.max_ticks else {
+            return;
+        };
+        while self.keyframes.len() > 1 && self.log.ticks.len() as u64 >= max_ticks + KEYFRAME_INTERVAL {
+            self.keyframes.remove(0);
+            self.log.initial = self.keyframes[0].clone();
+            self.log.ticks.drain(..KEYFRAME_INTERVAL as usize);
+        }
+    }
+
+    pub fn run(&mut self, simulation: &mut Simulation, n_ticks: u64) {
+        for _ in 0..n_ticks {
+            self.step(simulation);
+        }
+    }
+
+    pub fn log(&self) -> &ReplayLog {
+        &self.log
+    }
+
+    pub fn into_log(self) -> ReplayLog {
+        self.log
+    }
+
+    // un lecteur qui profite des photos déjà prises : aller à n'importe quel tick rejoue au plus KEYFRAME_INTERVAL ticks
+    pub fn player(&self, behaviors: BehaviorRegistry, build_policy: PolicyHandle) -> Player {
+        let mut player = Player::with_behaviors(self.log.clone(), behaviors, build_policy);
+        player.keyframes = self.keyframes.clone();
+        player
+    }
+}
+
+// relit un enregistrement : la simulation est déterministe, donc on la reconstruit depuis la photo
+// de départ et on vérifie à chaque tick qu'elle reproduit bien les évènements du log
+#[derive(Debug, Clone)]
+pub struct Player {
+    log: ReplayLog,
+    current: Simulation,
+    keyframes: Vec<Snapshot>,
+    behaviors: BehaviorRegistry,
+    build_policy: PolicyHandle,
+}
+
+impl Player {
+    pub fn new(log: ReplayLog) -> Self {
+        Self::with_behaviors(log, BehaviorRegistry::default(), PolicyHandle::default())
+    }
+
+    // à utiliser si la partie enregistrée utilisait des comportements ou une politique de construction personnalisés
+    pub fn with_behaviors(log: ReplayLog, behaviors: BehaviorRegistry, build_policy: PolicyHandle) -> Self {
+        let mut current = log.initial.clone().restore();
+        current.behaviors = behaviors.clone();
+        current.build_policy = build_policy.clone();
+        Self {
+            keyframes: vec![log.initial.clone()],
+            log,
+            current,
+            behaviors,
+            build_policy,
+        }
+    }
+
+    pub fn simulation(&self) -> &Simulation {
+        &self.current
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.current.tick
+    }
+
+    pub fn log(&self) -> &ReplayLog {
+        &self.log
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current.tick >= self.log.end_tick()
+    }
+
+    pub fn step_forward(&mut self) -> Result<&[Event], ReplayError> {
+        let tick = self.current.tick;
+        let index = match tick.checked_sub(self.log.start_tick()) {
+            Some(index) if (index as usize) < self.log.ticks.len() => index as usize,
+            _ => return Err(ReplayError::OutOfRange { tick }),
+        };
+
+        let events = step_with_events(&mut self.current);
+        if events != self.log.ticks[index].events {
+            return Err(ReplayError::Desync { tick });
+        }
+
+        let offset = self.current.tick - self.log.start_tick();
+        if offset.is_multiple_of(KEYFRAME_INTERVAL) && offset / KEYFRAME_INTERVAL == self.keyframes.len() as u64 {
+            self.keyframes.push(self.current.snapshot());
+        }
+
+        Ok(&self.log.ticks[index].events)
+    }
+
+    pub fn step_backward(&mut self) -> Result<(), ReplayError> {
+        match self.current.tick.checked_sub(1) {
+            Some(tick) => self.seek(tick),
+            None => Err(ReplayError::OutOfRange { tick: 0 }),
+        }
+    }
+
+    pub fn fast_forward(&mut self, n_ticks: u64) -> Result<(), ReplayError> {
+        let target = (self.current.tick + n_ticks).min(self.log.end_tick());
+        self.seek(target)
+    }
+
+    pub fn seek(&mut self, tick: u64) -> Result<(), ReplayError> {
+        if tick < self.log.start_tick() || tick > self.log.end_tick() {
+            return Err(ReplayError::OutOfRange { tick });
+        }
+
+        // on repart de la photo la plus proche si la cible est derrière nous ou loin devant
+        let keyframe = ((tick - self.log.start_tick()) / KEYFRAME_INTERVAL) as usize;
+        let keyframe = keyframe.min(self.keyframes.len() - 1);
+        let keyframe_tick = self.keyframes[keyframe].simulation.tick;
+        if tick < self.current.tick || keyframe_tick > self.current.tick {
+            self.current = self.keyframes[keyframe].clone().restore();
+            self.current.behaviors = self.behaviors.clone();
+            self.current.build_policy = self.build_policy.clone();
+        }
+
+        while self.current.tick < tick {
+            self.step_forward()?;
+        }
+        Ok(())
+    }
+}
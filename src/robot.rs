@@ -1,35 +1,96 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::BTreeSet;
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use crate::behavior::{Action, BehaviorRegistry, WorldView};
+use crate::belief::BeliefMap;
+use crate::chassis::Chassis;
+use crate::comms::CommsState;
+use crate::energy::{EnergyModel, HomeEstimate};
 use crate::map::Map;
+use crate::pathfinding::{self, Route};
+use crate::science::{self, Analysis, ScienceReport, ANALYSIS_TICKS};
+use crate::sensing::{self, FieldOfView};
+use crate::sync::SyncState;
+use crate::tasks::Task;
+use crate::traffic::{ReservationTable, TrafficState};
+use crate::world_events::STORM_SENSOR_RADIUS;
 use crate::tile::{Resource, TileContent};
 
-use std::collections::VecDeque;
+// nombre de ressources qu'un robot peut porter avant de rentrer à la station
+pub const DEFAULT_CARGO_CAPACITY: usize = 5;
 
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Robot {
     pub id: usize,
     pub position: (usize, usize),
     pub energy: u32,
-    pub module: Module,
+    // les modules embarqués, dans les limites du châssis (voir chassis.rs)
+    pub modules: Vec<Module>,
+    #[serde(default)]
+    pub chassis: Chassis,
     pub behavior: Behavior,
-    pub known_tiles: Vec<(usize, usize)>,
+    pub known_tiles: BTreeSet<(usize, usize)>,
+    pub cargo: Vec<Resource>,
+    pub cargo_capacity: usize,
+    // chemin en cache, recalculé seulement si le but change ou si la carte change sur le trajet
+    #[serde(default)]
+    pub route: Option<Route>,
+    // ce que le robot sait de la carte, uniquement par ses propres capteurs
+    #[serde(default)]
+    pub belief: BeliefMap,
+    #[serde(default)]
+    pub analysis: Option<Analysis>,
+    // rapports pas encore déposés à la station
+    #[serde(default)]
+    pub reports: Vec<ScienceReport>,
+    // les points d'intérêt déjà analysés par ce robot, pour ne pas y retourner
+    #[serde(default)]
+    pub analyzed_sites: BTreeSet<(usize, usize)>,
+    #[serde(default)]
+    pub reports_written: u32,
+    #[serde(default)]
+    pub sync: SyncState,
+    // messages échangés avec les autres robots (voir comms.rs)
+    #[serde(default)]
+    pub comms: CommsState,
+    // la tâche confiée par la station (voir tasks.rs)
+    #[serde(default)]
+    pub task: Option<Task>,
+    // le robot rentre recharger, il ne fait plus rien d'autre avant d'être à quai
+    #[serde(default)]
+    pub returning: bool,
+    // plus assez d'énergie pour faire un pas, même s'il en reste un peu : il faut venir le chercher
+    #[serde(default)]
+    pub stranded: bool,
+    // borne haute du chemin de retour, tenue par le planificateur (voir energy.rs)
+    #[serde(default)]
+    pub home: HomeEstimate,
+    // attentes et détours quand la circulation est réglée (voir traffic.rs)
+    #[serde(default)]
+    pub traffic: TrafficState,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Module {
     Analysis,
     Mining,
     Imaging,
+    // batterie d'appoint
+    Battery,
+    // soute supplémentaire
+    CargoBay,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Behavior {
     Exploration,
     ResourceCollection,
     ScientificInterest,
+    // repasse voir les tuiles dont les informations sont les plus anciennes
+    Patrol,
+    // stratégie enregistrée sous ce nom dans le BehaviorRegistry
+    Custom(String),
 }
 
 impl Robot {
@@ -38,112 +99,341 @@ impl Robot {
             id,
             position,
             energy,
-            module,
+            modules: vec![module],
+            chassis: Chassis::default(),
             behavior,
-            known_tiles: vec![]
+            known_tiles: BTreeSet::new(),
+            cargo: vec![],
+            cargo_capacity: DEFAULT_CARGO_CAPACITY,
+            route: None,
+            belief: BeliefMap::default(),
+            analysis: None,
+            reports: vec![],
+            analyzed_sites: BTreeSet::new(),
+            reports_written: 0,
+            sync: SyncState::default(),
+            comms: CommsState::default(),
+            task: None,
+            returning: false,
+            stranded: false,
+            home: HomeEstimate::default(),
+            traffic: TrafficState::default(),
         }
     }
+
+    pub fn is_cargo_full(&self) -> bool {
+        self.cargo.len() >= self.cargo_capacity
+    }
     
     // on gere les comportements des petits robots
-    pub fn perform_action(&mut self, map: &mut Map, station_position: (usize, usize)) {
-        if self.energy == 0 {
-            println!("Robot {} has no energy and needs to recharge", self.id);
-            self.move_towards_goal(map, station_position);
-            if self.position == station_position {
-                self.refill_energy();
-            }
-            return;
+    // tout l'aléatoire passe par rng et le temps par tick, pour que la simulation soit rejouable
+    pub fn perform_action<R: Rng>(&mut self, map: &mut Map, station_position: (usize, usize), rng: &mut R, tick: u64) {
+        self.perform_action_with(&BehaviorRegistry::default(), &EnergyModel::default(), map, station_position, rng, tick);
+    }
+
+    // pareil, mais en allant chercher les comportements personnalisés dans le registre
+    pub fn perform_action_with<R: Rng>(
+        &mut self,
+        behaviors: &BehaviorRegistry,
+        energy: &EnergyModel,
+        map: &mut Map,
+        station_position: (usize, usize),
+        rng: &mut R,
+        tick: u64,
+    ) {
+        let action = self.choose_action(behaviors, energy, map, station_position, rng, tick);
+        self.carry_out(action, energy, map, None, rng, tick);
+    }
+
+    // le robot regarde autour de lui, lit ses messages et décide quoi faire ce tick
+    pub fn choose_action<R: Rng>(
+        &mut self,
+        behaviors: &BehaviorRegistry,
+        energy: &EnergyModel,
+        map: &mut Map,
+        station_position: (usize, usize),
+        rng: &mut R,
+        tick: u64,
+    ) -> Action {
+        if self.energy >= energy.sense_cost {
+            self.sense(map, tick);
+            self.energy -= energy.sense_cost;
         }
+        self.read_inbox(tick);
 
-        match self.behavior {
-            Behavior::Exploration => self.explore(map),
-            Behavior::ResourceCollection => self.collect_resource(map),
-            Behavior::ScientificInterest => self.investigate(map),
+        if !self.returning && energy.return_planner.should_return(self, station_position, energy) {
+            self.returning = true;
         }
 
-        self.energy -= 1; 
+        let action = if self.returning {
+            Action::MoveTowards(station_position)
+        } else {
+            match behaviors.resolve(&self.behavior) {
+                Some(strategy) => {
+                    let world = WorldView { station_position, tick };
+                    strategy.decide(self, &world, rng)
+                }
+                // comportement personnalisé pas enregistré : le robot attend
+                None => Action::Idle,
+            }
+        };
+
+        // pas assez d'énergie pour l'action : le robot attend sur place
+        if energy.action_cost(&action, self.cargo.len()) > self.energy {
+            return Action::Idle;
+        }
+        action
     }
 
-    fn find_random_goal(&self, map: &Map) -> (usize, usize) {
-        let mut rng = rand::thread_rng();
-        loop {
-            let x = rng.gen_range(0..map.width);
-            let y = rng.gen_range(0..map.height);
-            if map.tiles[y][x].content != TileContent::Obstacle {
-                return (x, y);
+    // chaque action est payée selon le modèle d'énergie, une fois qu'elle a vraiment eu lieu
+    pub fn carry_out<R: Rng>(
+        &mut self,
+        action: Action,
+        energy: &EnergyModel,
+        map: &mut Map,
+        reservations: Option<&mut ReservationTable>,
+        rng: &mut R,
+        tick: u64,
+    ) {
+        let position_before = self.position;
+        let cargo_before = self.cargo.len();
+        let performed = self.execute(action, map, reservations, rng, tick);
+        let cost = match action {
+            Action::MoveTowards(_) | Action::MoveRandomly | Action::ExploreTowards(_) if self.position != position_before => {
+                energy.action_cost(&action, cargo_before)
             }
+            Action::Mine | Action::Analyze if performed => energy.action_cost(&action, cargo_before),
+            _ => energy.idle_cost,
+        };
+        self.energy = self.energy.saturating_sub(cost);
+        if self.position != position_before {
+            self.home.walked += 1;
         }
+
+        // s'il ne peut même plus faire un pas, il reste en panne jusqu'à ce qu'on vienne le chercher
+        self.stranded = energy.is_stranded(self);
     }
 
-    pub fn move_towards_goal(&mut self, map: &Map, goal: (usize, usize)) {
-        if let Some(path) = move_using_bfs(map, self.position, goal) {
-            if path.len() > 1 {
-                let next_step = path[1];
-                self.position = next_step;
+    // renvoie vrai si l'action a eu lieu (le robot a bougé, miné, avancé son analyse)
+    // si la circulation est réglée, la table dit quelles tuiles sont déjà prises ce tick
+    pub fn execute<R: Rng + ?Sized>(
+        &mut self,
+        action: Action,
+        map: &mut Map,
+        reservations: Option<&mut ReservationTable>,
+        rng: &mut R,
+        tick: u64,
+    ) -> bool {
+        let position_before = self.position;
+        let performed = match action {
+            Action::Idle => false,
+            Action::MoveTowards(goal) => {
+                self.move_towards_goal(map, reservations.as_deref(), goal, rng);
+                self.position != position_before
+            }
+            Action::MoveRandomly => {
+                self.move_randomly(map, reservations.as_deref(), rng);
+                self.position != position_before
             }
+            Action::ExploreTowards(goal) => {
+                println!("Robot {} exploring at position {:?}", self.id, self.position);
+                self.move_towards_goal(map, reservations.as_deref(), goal, rng);
+                self.sense(map, tick);
+                self.position != position_before
+            }
+            Action::Mine => self.mine(map, tick),
+            Action::Analyze => self.analyze(map, rng, tick),
+        };
+        // la nouvelle tuile est réservée pour ce tick, les robots suivants devront passer ailleurs
+        if let Some(reservations) = reservations {
+            if self.position != position_before {
+                reservations.move_robot(self.id, position_before, self.position);
+            }
+            let planned = self.route.as_ref().map(|route| route.remaining().to_vec()).unwrap_or_default();
+            reservations.plan(self.id, planned);
+        }
+        performed
+    }
+
+    // tout ce que le capteur du module voit est noté dans la croyance du robot et marqué exploré sur la carte
+    // dans une tempête de poussière le robot ne voit plus que sa tuile
+    pub fn sense(&mut self, map: &mut Map, tick: u64) {
+        self.belief.ensure_size(map.width, map.height);
+        let fov = if map.in_storm(self.position) {
+            FieldOfView { radius: STORM_SENSOR_RADIUS, line_of_sight: false }
         } else {
-            self.move_randomly(map);
+            self.field_of_view()
+        };
+        for (x, y) in sensing::visible_tiles(map, self.position, fov) {
+            if let Some(tile) = map.tile_at_mut(x, y) {
+                tile.explored = true;
+                tile.timestamp = tick;
+                self.belief.observe_deposit(x, y, tile.content, tile.quantity, tick);
+                self.known_tiles.insert((x, y));
+            }
         }
     }
 
-    pub fn find_resource(&self, map: &Map) -> Option<(usize, usize)> {
-        for y in 0..map.height {
-            for x in 0..map.width {
-                if let TileContent::Resource(_) = map.tiles[y][x].content {
-                    if !self.known_tiles.contains(&(x, y)) {
-                        return Some((x, y));
-                    }
-                }
+    // une observation venue d'un autre robot, gardée seulement si elle est plus récente que la nôtre
+    pub fn receive_observation(&mut self, x: usize, y: usize, content: TileContent, tick: u64) {
+        if self.belief.observation(x, y).is_none_or(|observation| observation.tick < tick) {
+            self.belief.observe(x, y, content, tick);
+            self.known_tiles.insert((x, y));
+        }
+    }
+
+    // une tuile au hasard qu'on ne sait pas être un obstacle
+    pub fn find_random_goal<R: Rng + ?Sized>(&self, rng: &mut R) -> (usize, usize) {
+        if self.belief.width == 0 || self.belief.height == 0 {
+            return self.position;
+        }
+        loop {
+            let x = rng.gen_range(0..self.belief.width);
+            let y = rng.gen_range(0..self.belief.height);
+            if self.belief.content_at(x, y) != Some(TileContent::Obstacle) {
+                return (x, y);
             }
         }
-        None 
     }
 
-    fn find_point_of_interest(&self, map: &Map) -> (usize, usize) {
-        for y in 0..map.height {
-            for x in 0..map.width {
-                if let TileContent::Resource(Resource::PlaceOfInterest) = map.tiles[y][x].content {
-                    return (x, y);
+    // le chemin est calculé sur ce que le robot croit savoir, le pas est vérifié sur la vraie carte
+    // et, si la circulation est réglée, on ne marche pas sur un autre robot
+    pub fn move_towards_goal<R: Rng + ?Sized>(
+        &mut self,
+        map: &Map,
+        reservations: Option<&ReservationTable>,
+        goal: (usize, usize),
+        rng: &mut R,
+    ) {
+        self.belief.ensure_size(map.width, map.height);
+        let still_valid = self.route.as_ref().is_some_and(|route| route.is_valid(&self.belief, self.position, goal));
+        if !still_valid {
+            self.route = pathfinding::find_path(&self.belief, self.position, goal);
+        }
+
+        match &mut self.route {
+            Some(route) => {
+                if let Some(next_step) = route.peek() {
+                    let blocker = reservations.and_then(|reservations| reservations.blocker(next_step, self.id).map(|id| (reservations, id)));
+                    if map.tile_at(next_step.0, next_step.1).is_none_or(|tile| tile.content == TileContent::Obstacle) {
+                        // obstacle inconnu sur le chemin, on recalculera au prochain tick
+                        self.route = None;
+                    } else if let Some((reservations, blocker)) = blocker {
+                        self.give_way(map, reservations, goal, blocker, rng);
+                    } else {
+                        route.advance();
+                        self.position = next_step;
+                        self.traffic.waiting = 0;
+                    }
                 }
             }
+            None => self.move_randomly(map, reservations, rng),
         }
-        self.position
     }
 
-    fn explore(&mut self, map: &mut Map) {
-        println!("Robot {} exploring at position {:?}", self.id, self.position);
-        let goal = self.find_random_goal(map);
-        self.move_towards_goal(map, goal);
-        self.mark_explored(map);
+    // le gisement connu le plus intéressant qu'on peut ramener à la station (énergie, minerai)
+    // et qu'aucun autre robot n'a réservé
+    pub fn find_resource(&self) -> Option<(usize, usize)> {
+        let space = self.cargo_capacity.saturating_sub(self.cargo.len());
+        self.belief.best_deposit(self.position, space, |position, resource| {
+            resource.is_minable() && !self.comms.is_claimed_by_other(position, self.id)
+        })
     }
 
-    fn collect_resource(&mut self, map: &mut Map) {
-        println!("Robot {} collecting resources at position {:?}", self.id, self.position);
-        if !self.known_tiles.contains(&self.position) {
-                self.mine(map);
-        }
-        self.move_randomly(map);
+    // le point d'intérêt connu le plus proche qu'on n'a pas encore analysé
+    pub fn find_point_of_interest(&self) -> Option<(usize, usize)> {
+        self.belief.nearest_resource(self.position, |position, resource| {
+            resource == Resource::PlaceOfInterest && !self.analyzed_sites.contains(&position)
+        })
     }
 
-    fn investigate(&mut self, map: &mut Map) {
-        println!("Robot {} investigating at position {:?}", self.id, self.position);
-        let goal = self.find_point_of_interest(map);
-        self.move_towards_goal(map, goal);
-        // self.analyze(); 
+    // un robot avec une foreuse et de la place, posé sur de l'énergie ou du minerai
+    pub fn can_mine(&self) -> bool {
+        if self.mining_rate() == 0 || self.is_cargo_full() {
+            return false;
+        }
+        matches!(
+            self.belief.content_at(self.position.0, self.position.1),
+            Some(TileContent::Resource(resource)) if resource.is_minable()
+        )
     }
 
-    fn mine(&self, map: &mut Map) {
-        if let Module::Mining = self.module {
-            println!("Robot {} mining at position {:?}", self.id, self.position);
-            if let Some(resource) = map.retrieve_resource_at(self.position.0, self.position.1) {
-                println!("Collected resource: {:?}", resource);
+    // renvoie true si au moins une ressource a été mise dans la soute
+    // chaque foreuse embarquée extrait une unité par tick, tant qu'il reste de la place et de quoi extraire
+    fn mine(&mut self, map: &mut Map, tick: u64) -> bool {
+        if !self.can_mine() {
+            return false;
+        }
+        println!("Robot {} mining at position {:?}", self.id, self.position);
+        let mut mined = false;
+        for _ in 0..self.mining_rate() {
+            if self.is_cargo_full() {
+                break;
             }
+            let Some(resource) = map.retrieve_resource_at(self.position.0, self.position.1) else {
+                break;
+            };
+            println!("Collected resource: {:?}", resource);
+            self.cargo.push(resource);
+            let (content, quantity) = match map.deposit_at(self.position.0, self.position.1) {
+                Some((resource, quantity)) => (TileContent::Resource(resource), quantity),
+                None => (TileContent::Empty, 0),
+            };
+            self.belief.observe_deposit(self.position.0, self.position.1, content, quantity, tick);
+            mined = true;
         }
+        mined
     }
 
-    
-    fn move_randomly(&mut self, map: &Map) {
-        let mut rng = rand::thread_rng();
+    pub fn is_analyzing(&self) -> bool {
+        self.analysis.is_some_and(|analysis| analysis.location == self.position)
+    }
+
+    // un robot d'analyse posé sur un point d'intérêt qu'il n'a pas encore étudié
+    pub fn can_analyze(&self) -> bool {
+        if !self.has_module(Module::Analysis) || self.analyzed_sites.contains(&self.position) {
+            return false;
+        }
+        self.belief.content_at(self.position.0, self.position.1) == Some(TileContent::Resource(Resource::PlaceOfInterest))
+    }
+
+    // un tick d'analyse, le rapport est produit au dernier
+    fn analyze<R: Rng + ?Sized>(&mut self, map: &Map, rng: &mut R, tick: u64) -> bool {
+        let on_point_of_interest = map
+            .tile_at(self.position.0, self.position.1)
+            .is_some_and(|tile| tile.content == TileContent::Resource(Resource::PlaceOfInterest));
+        if !self.can_analyze() || !on_point_of_interest {
+            self.analysis = None;
+            return false;
+        }
+
+        // si le robot a été interrompu ailleurs, on recommence depuis le début
+        let ticks_left = match self.analysis {
+            Some(analysis) if analysis.location == self.position => analysis.ticks_left,
+            _ => ANALYSIS_TICKS,
+        } - 1;
+
+        if ticks_left > 0 {
+            self.analysis = Some(Analysis { location: self.position, ticks_left });
+            return true;
+        }
+
+        let report = ScienceReport {
+            // unique tant que les robots ont des id différents
+            id: ((self.id as u64) << 32) | self.reports_written as u64,
+            location: self.position,
+            tick,
+            findings: science::generate_findings(rng),
+        };
+        self.reports_written += 1;
+        self.reports.push(report);
+        self.analyzed_sites.insert(self.position);
+        self.analysis = None;
+        true
+    }
+
+
+    pub fn move_randomly<R: Rng + ?Sized>(&mut self, map: &Map, reservations: Option<&ReservationTable>, rng: &mut R) {
         let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
         for _ in 0..4 {  
@@ -151,68 +441,35 @@ impl Robot {
             let new_x = (self.position.0 as isize + dx).clamp(0, (map.width - 1) as isize) as usize;
             let new_y = (self.position.1 as isize + dy).clamp(0, (map.height - 1) as isize) as usize;
 
-            if map.tiles[new_y][new_x].content != TileContent::Obstacle {
+            let free = reservations.is_none_or(|reservations| reservations.is_free_for((new_x, new_y), self.id));
+            if map.tiles[new_y][new_x].content != TileContent::Obstacle && free {
                 self.position = (new_x, new_y);
                 break;
             }
         }
     }
 
-    pub fn mark_explored(&mut self, map: &mut Map) {
+    pub fn mark_explored(&mut self, map: &mut Map, tick: u64) {
         if let Some(tile) = map.tile_at_mut(self.position.0, self.position.1) {
             tile.explored = true;
-            tile.timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            tile.timestamp = tick;
             self.mark_tile_as_known(self.position.0, self.position.1);
         }
     }
 
     pub fn mark_tile_as_known(&mut self, x: usize, y: usize) {
-        if !self.known_tiles.contains(&(x, y)) {
-            self.known_tiles.push((x, y));
-        }
+        self.known_tiles.insert((x, y));
+    }
+
+    // chaque module apporte sa batterie
+    pub fn battery_capacity(&self) -> u32 {
+        self.modules.iter().map(Module::battery_capacity).sum()
     }
 
     pub fn refill_energy(&mut self) {
-        self.energy = 80; 
+        self.energy = self.battery_capacity();
+        self.returning = false;
+        self.stranded = false;
         println!("Robot {} is recharged", self.id);
     }
 }
-
- fn move_using_bfs(map: &Map, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
-    let mut queue = VecDeque::new();
-    let mut came_from = std::collections::HashMap::new();
-
-    queue.push_back(start);
-    came_from.insert(start, None);
-
-    while let Some(current) = queue.pop_front() {
-        if current == goal {
-            let mut path = Vec::new();
-            let mut current = current;
-            while let Some(&Some(prev)) = came_from.get(&current) {
-                path.push(current);
-                current = prev;
-            }
-            path.reverse();
-            return Some(path);
-        }
-
-        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-        for &direction in &directions {
-            let next = (
-                (current.0 as isize + direction.0) as usize,
-                (current.1 as isize + direction.1) as usize,
-            );
-
-            if map.check_bounds(next.0, next.1)
-                && map.tiles[next.1][next.0].content != TileContent::Obstacle
-                && !came_from.contains_key(&next)
-            {
-                queue.push_back(next);
-                came_from.insert(next, Some(current));
-            }
-        }
-    }
-
-    None
-}
\ No newline at end of file
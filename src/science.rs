@@ -0,0 +1,54 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// une analyse occupe le robot plusieurs ticks sur le point d'intérêt
+pub const ANALYSIS_TICKS: u32 = 5;
+pub const MAX_FINDINGS: usize = 3;
+pub const MAX_FINDING_VALUE: u32 = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FindingKind {
+    Mineral,
+    Water,
+    Fossil,
+    Anomaly,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub value: u32,
+}
+
+// analyse en cours : où, et combien de ticks il reste
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Analysis {
+    pub location: (usize, usize),
+    pub ticks_left: u32,
+}
+
+// le résultat d'une analyse, il ne compte qu'une fois déposé à la station
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScienceReport {
+    pub id: u64,
+    pub location: (usize, usize),
+    pub tick: u64,
+    pub findings: Vec<Finding>,
+}
+
+impl ScienceReport {
+    pub fn score(&self) -> u32 {
+        self.findings.iter().map(|finding| finding.value).sum()
+    }
+}
+
+pub fn generate_findings<R: Rng + ?Sized>(rng: &mut R) -> Vec<Finding> {
+    let kinds = [FindingKind::Mineral, FindingKind::Water, FindingKind::Fossil, FindingKind::Anomaly];
+    let count = rng.gen_range(1..=MAX_FINDINGS);
+    (0..count)
+        .map(|_| Finding {
+            kind: kinds[rng.gen_range(0..kinds.len())],
+            value: rng.gen_range(1..=MAX_FINDING_VALUE),
+        })
+        .collect()
+}
@@ -0,0 +1,84 @@
+use crate::map::Map;
+use crate::robot::Module;
+use crate::tile::TileContent;
+
+// portée du châssis de base, tout robot voit au moins ses voisines directes
+pub const BASE_SENSOR_RADIUS: usize = 1;
+pub const IMAGING_SENSOR_RADIUS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldOfView {
+    pub radius: usize,
+    // si vrai, les obstacles cachent ce qu'il y a derrière eux
+    pub line_of_sight: bool,
+}
+
+impl Module {
+    pub fn field_of_view(&self) -> FieldOfView {
+        match self {
+            Module::Imaging => FieldOfView {
+                radius: IMAGING_SENSOR_RADIUS,
+                line_of_sight: true,
+            },
+            Module::Analysis | Module::Mining | Module::Battery | Module::CargoBay => FieldOfView {
+                radius: BASE_SENSOR_RADIUS,
+                line_of_sight: false,
+            },
+        }
+    }
+}
+
+// vrai si aucun obstacle ne se trouve strictement entre from et to (tracé de Bresenham)
+pub fn has_line_of_sight(map: &Map, from: (usize, usize), to: (usize, usize)) -> bool {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (tx, ty) = (to.0 as isize, to.1 as isize);
+    let dx = (tx - x).abs();
+    let dy = -(ty - y).abs();
+    let sx = if x < tx { 1 } else { -1 };
+    let sy = if y < ty { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        if (x, y) == (tx, ty) {
+            return true;
+        }
+        if (x, y) != (from.0 as isize, from.1 as isize)
+            && map.tiles[y as usize][x as usize].content == TileContent::Obstacle
+        {
+            return false;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+// toutes les tuiles que voit un capteur posé en origin (disque de rayon radius)
+pub fn visible_tiles(map: &Map, origin: (usize, usize), fov: FieldOfView) -> Vec<(usize, usize)> {
+    let radius = fov.radius as isize;
+    let mut visible = vec![];
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+            let (Some(x), Some(y)) = (origin.0.checked_add_signed(dx), origin.1.checked_add_signed(dy)) else {
+                continue;
+            };
+            if !map.check_bounds(x, y) {
+                continue;
+            }
+            if fov.line_of_sight && !has_line_of_sight(map, origin, (x, y)) {
+                continue;
+            }
+            visible.push((x, y));
+        }
+    }
+    visible
+}
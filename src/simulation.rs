@@ -0,0 +1,213 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::behavior::BehaviorRegistry;
+use crate::comms::{self, CommsConfig, Network};
+use crate::connectivity::{self, Connectivity, ConnectivityReport, StationPlacement};
+use crate::energy::{EnergyModel, EnergyStats};
+use crate::map::{Map, MapGenConfig};
+use crate::production::PolicyHandle;
+use crate::robot::{Behavior, Module, Robot};
+use crate::station::Station;
+use crate::sync::{self, SyncConfig, SyncMessage};
+use crate::traffic::{ReservationTable, TrafficConfig, TrafficStats};
+use crate::world_events::{WorldEvents, WorldEventsConfig};
+
+// le moteur de simulation, sans aucune dépendance à ggez
+// main.rs ne fait plus que dessiner cet état
+// tout l'aléatoire vient de rng (graine unique) et le temps de tick : même graine => même partie
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub map: Map,
+    pub station: Station,
+    pub tick: u64,
+    pub rng: ChaCha8Rng,
+    #[serde(default)]
+    pub sync_config: SyncConfig,
+    // les échanges robot <-> station du dernier tick
+    #[serde(default)]
+    pub sync_messages: Vec<SyncMessage>,
+    #[serde(default)]
+    pub energy_model: EnergyModel,
+    #[serde(default)]
+    pub energy_stats: EnergyStats,
+    #[serde(default)]
+    pub comms_config: CommsConfig,
+    #[serde(default)]
+    pub network: Network,
+    // un robot par tuile hors de la station, désactivé par défaut
+    #[serde(default)]
+    pub traffic_config: TrafficConfig,
+    // régénération, nouveaux points d'intérêt, tempêtes et éboulements, désactivés par défaut
+    #[serde(default)]
+    pub world_events_config: WorldEventsConfig,
+    #[serde(default)]
+    pub world_events: WorldEvents,
+    // pas sauvegardé : les comportements personnalisés sont à réenregistrer après un restore
+    #[serde(skip)]
+    pub behaviors: BehaviorRegistry,
+    // quoi construire ensuite, à redonner aussi après un restore
+    #[serde(skip)]
+    pub build_policy: PolicyHandle,
+}
+
+impl Simulation {
+    pub fn new(map: Map, station: Station, seed: u64) -> Self {
+        Self {
+            map,
+            station,
+            tick: 0,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            sync_config: SyncConfig::default(),
+            sync_messages: vec![],
+            energy_model: EnergyModel::default(),
+            energy_stats: EnergyStats::default(),
+            comms_config: CommsConfig::default(),
+            network: Network::default(),
+            traffic_config: TrafficConfig::default(),
+            world_events_config: WorldEventsConfig::default(),
+            world_events: WorldEvents::default(),
+            behaviors: BehaviorRegistry::default(),
+            build_policy: PolicyHandle::default(),
+        }
+    }
+
+    // monde par défaut : carte générée sans poche isolée, station au centre et 3 robots
+    pub fn generate(width: usize, height: usize, seed: u64) -> Option<Self> {
+        let config = MapGenConfig {
+            connectivity: Connectivity::CarveCorridors,
+            ..MapGenConfig::default()
+        };
+        Self::generate_with(width, height, seed, &config, StationPlacement::CentreMost).map(|(simulation, _)| simulation)
+    }
+
+    // pareil avec une config de génération, renvoie aussi le rapport de connectivité
+    pub fn generate_with(width: usize, height: usize, seed: u64, config: &MapGenConfig, placement: StationPlacement) -> Option<(Self, ConnectivityReport)> {
+        let (map, report) = connectivity::generate_world(width, height, seed, config, placement)?;
+        Some((Self::from_map(map, report.station, seed), report))
+    }
+
+    // une carte écrite à la main (Map::from_ascii) avec les 3 robots de départ
+    pub fn from_map(map: Map, station_position: (usize, usize), seed: u64) -> Self {
+        let mut station = Station::new(station_position);
+
+        // 3 robots avec des rôles différents, batterie pleine au départ
+        let roles = [
+            (Module::Analysis, Behavior::ScientificInterest),
+            (Module::Mining, Behavior::ResourceCollection),
+            (Module::Mining, Behavior::ResourceCollection),
+        ];
+        for (id, (module, behavior)) in (1..).zip(roles) {
+            let energy = module.battery_capacity();
+            station.robots.push(Robot::new(id, station.position, energy, module, behavior));
+        }
+
+        Self::new(map, station, seed)
+    }
+
+    // un tick complet de la simulation
+    pub fn step(&mut self) {
+        if self.world_events_config.enabled {
+            self.update_world();
+        }
+        self.station.plan_tasks(self.tick);
+        self.update_robots();
+        self.station.update_production(self.build_policy.0.as_ref());
+        if self.comms_config.enabled {
+            self.exchange_messages();
+        }
+        self.tick += 1;
+    }
+
+    pub fn run(&mut self, n_ticks: u64) {
+        for _ in 0..n_ticks {
+            self.step();
+        }
+    }
+
+    // robots en panne sèche loin de la station en ce moment
+    pub fn stranded_robots(&self) -> usize {
+        self.station.robots.iter().filter(|robot| robot.stranded && robot.position != self.station.position).count()
+    }
+
+    // attentes, détours et robots qui se sont écartés depuis le début de la partie
+    pub fn traffic_stats(&self) -> TrafficStats {
+        self.station.robots.iter().fold(TrafficStats::default(), |stats, robot| TrafficStats {
+            waits: stats.waits + robot.traffic.waits,
+            detours: stats.detours + robot.traffic.detours,
+            yields: stats.yields + robot.traffic.yields,
+        })
+    }
+
+    fn update_robots(&mut self) {
+        let mut robots_to_refill = vec![];
+        // les robots jouent dans l'ordre de la flotte, les premiers ont la priorité
+        // la table n'existe que le temps du tour des robots
+        let mut reservations = self.traffic_config.enabled.then(|| {
+            ReservationTable::from_robots(&self.map, self.station.position, self.traffic_config.patience, &self.station.robots)
+        });
+
+        for robot in &mut self.station.robots {
+            let (stranded_before, returning_before) = (robot.stranded, robot.returning);
+            let action = robot.choose_action(&self.behaviors, &self.energy_model, &mut self.map, self.station.position, &mut self.rng, self.tick);
+            robot.carry_out(action, &self.energy_model, &mut self.map, reservations.as_mut(), &mut self.rng, self.tick);
+            robot.call_for_help(self.tick);
+            if robot.returning && !returning_before {
+                self.energy_stats.returns += 1;
+            }
+            if robot.stranded && !stranded_before && robot.position != self.station.position {
+                self.energy_stats.ran_dry += 1;
+            }
+            if robot.position == self.station.position {
+                robots_to_refill.push(robot.id);
+            }
+        }
+
+        for robot_id in self.station.deliver_rescues() {
+            self.network.stats.rescues += 1;
+            comms::forget_distress(&mut self.station.robots, &mut self.network, robot_id);
+        }
+        self.collect_and_refill_robots(robots_to_refill);
+    }
+
+    fn collect_and_refill_robots(&mut self, robots_to_refill: Vec<usize>) {
+        // on sort les robots de la station le temps de la collecte pour pouvoir les synchroniser
+        let mut robots = std::mem::take(&mut self.station.robots);
+        self.sync_messages.clear();
+
+        for robot in robots.iter_mut().filter(|r| robots_to_refill.contains(&r.id)) {
+            let messages = sync::sync(&mut self.station, robot, &self.sync_config, self.tick);
+            self.sync_messages.extend(messages);
+            self.station.unload_cargo(robot);
+            self.station.receive_reports(robot);
+            // la soute vient d'être vidée, on peut changer les modules avant de recharger
+            match self.station.refit(robot) {
+                Ok(true) => self.station.refit_stats.refitted += 1,
+                Ok(false) => {}
+                Err(_) => self.station.refit_stats.failed += 1,
+            }
+            robot.refill_energy();
+        }
+        for robot_id in robots_to_refill {
+            comms::forget_distress(&mut robots, &mut self.network, robot_id);
+        }
+
+        self.station.robots = robots;
+    }
+
+    // le monde change avant que les robots jouent, ils le découvriront avec leurs capteurs
+    fn update_world(&mut self) {
+        let mut occupied: Vec<_> = self.station.robots.iter().map(|robot| robot.position).collect();
+        occupied.push(self.station.position);
+        self.world_events.update(&mut self.map, &self.world_events_config, &occupied, &mut self.rng, self.tick);
+    }
+
+    // les robots disent ce qu'ils ont vu, les messages seront lus au tick suivant
+    fn exchange_messages(&mut self) {
+        for robot in &mut self.station.robots {
+            robot.compose_messages(self.tick);
+        }
+        comms::exchange(&mut self.station.robots, &mut self.network, &self.comms_config, &mut self.rng, self.tick);
+    }
+}
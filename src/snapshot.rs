@@ -0,0 +1,141 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Simulation;
+
+// format des snapshots : un fichier écrit par une autre version est refusé au chargement
+pub const SNAPSHOT_VERSION: u32 = 13;
+
+// en-tête du format binaire, suivi de la version puis du contenu bincode
+const BINARY_MAGIC: &[u8; 4] = b"EERS";
+
+// une photo complète du monde : carte, station, robots, base de connaissances, tick et état du rng
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub version: u32,
+    pub simulation: Simulation,
+}
+
+// juste la version, pour la vérifier avant de tout désérialiser
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    InvalidFormat,
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "snapshot io error: {}", err),
+            SnapshotError::Json(err) => write!(f, "invalid json snapshot: {}", err),
+            SnapshotError::Binary(err) => write!(f, "invalid binary snapshot: {}", err),
+            SnapshotError::InvalidFormat => write!(f, "not an eerea snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {} (expected {})", version, SNAPSHOT_VERSION)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        SnapshotError::Json(err)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        SnapshotError::Binary(err)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SnapshotError> {
+    if version == SNAPSHOT_VERSION {
+        Ok(())
+    } else {
+        Err(SnapshotError::UnsupportedVersion(version))
+    }
+}
+
+impl Snapshot {
+    pub fn capture(simulation: &Simulation) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            simulation: simulation.clone(),
+        }
+    }
+
+    pub fn restore(self) -> Simulation {
+        self.simulation
+    }
+
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        let header: SnapshotHeader = serde_json::from_str(json)?;
+        check_version(header.version)?;
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend(bincode::serialize(&self.simulation)?);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < 8 || &bytes[..4] != BINARY_MAGIC {
+            return Err(SnapshotError::InvalidFormat);
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        check_version(version)?;
+        let simulation = bincode::deserialize(&bytes[8..])?;
+        Ok(Self { version, simulation })
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+impl Simulation {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self)
+    }
+}
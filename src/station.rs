@@ -1,20 +1,44 @@
-use crate::map::Map;
+use serde::{Deserialize, Serialize};
+
+use crate::chassis::{RefitOrder, RefitStats};
+use crate::knowledge::{KnowledgeBase, KnownTile};
+use crate::production::ProductionQueue;
 use crate::robot::{Robot, Module, Behavior};
+use crate::science::ScienceReport;
+use crate::sync::SyncStats;
+use crate::tasks::TaskBoard;
+use crate::tile::Resource;
+
+// énergie rapportée à la station par unité de Resource::Energy livrée
+pub const ENERGY_PER_DELIVERY: u32 = 25;
 
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Station {
     pub position: (usize, usize),
     pub energy: u32,
+    pub ore: u32,
     pub robots: Vec<Robot>,
-    pub known_tiles: Vec<KnownTile>, 
-}
-
-#[derive(Debug)]
-pub struct KnownTile {
-    pub x: usize,
-    pub y: usize,
-    pub timestamp: u64,
+    // la carte partagée, alimentée par les robots qui reviennent
+    pub known_tiles: KnowledgeBase,
+    #[serde(default)]
+    pub science_reports: Vec<ScienceReport>,
+    #[serde(default)]
+    pub science_score: u32,
+    #[serde(default)]
+    pub sync_stats: SyncStats,
+    #[serde(default)]
+    pub task_board: TaskBoard,
+    #[serde(default)]
+    pub production: ProductionQueue,
+    // changements de modules demandés, appliqués quand le robot est à quai (voir chassis.rs)
+    #[serde(default)]
+    pub refits: Vec<RefitOrder>,
+    // modules démontés, réutilisables sans les refabriquer
+    #[serde(default)]
+    pub spare_modules: Vec<Module>,
+    #[serde(default)]
+    pub refit_stats: RefitStats,
 }
 
 impl Station {
@@ -22,50 +46,76 @@ impl Station {
         Self {
             position,
             energy: 0,
+            ore: 0,
             robots: vec![],
-            known_tiles: vec![],
+            known_tiles: KnowledgeBase::default(),
+            science_reports: vec![],
+            science_score: 0,
+            sync_stats: SyncStats::default(),
+            task_board: TaskBoard::default(),
+            production: ProductionQueue::default(),
+            refits: vec![],
+            spare_modules: vec![],
+            refit_stats: RefitStats::default(),
         }
     }
 
     // petite fonction de collecte des données des robots qu reviennent à la station
-    pub fn collect_data(&mut self, robot: &Robot, map: &Map) {
-        for &(x, y) in &robot.known_tiles {
-            if let Some(tile) = map.tile_at(x, y) {
-                if tile.explored {
-                    let known_tile = self.known_tiles.iter_mut().find(|t| t.x == x && t.y == y);
-                    match known_tile {
-                        Some(existing_tile) => {
-                            if tile.timestamp > existing_tile.timestamp {
-                                existing_tile.timestamp = tile.timestamp;
-                            }
-                        }
-                        None => {
-                            self.known_tiles.push(KnownTile { x, y, timestamp: tile.timestamp });
-                        }
-                    }
+    // on prend ce que le robot a réellement observé, avec la date de l'observation
+    pub fn collect_data(&mut self, robot: &Robot) {
+        let belief = &robot.belief;
+        if !self.known_tiles.accepts(belief.width, belief.height) {
+            return;
+        }
+        for y in 0..belief.height {
+            for x in 0..belief.width {
+                if let Some(observation) = belief.observation(x, y) {
+                    let known_tile = KnownTile { content: observation.content, observer: robot.id, tick: observation.tick };
+                    self.known_tiles.merge(x, y, known_tile);
                 }
             }
         }
     }
-
     
 
-    // la station doit pouvoir créer des robots 
-    pub fn create_robot(&mut self, id: usize, position: (usize, usize), module: Module, behavior: Behavior) -> Robot {
-        let robot = Robot::new(id, position, 100, module, behavior);
-        self.robots.push(robot.clone());
-        robot
-    }
-
-    // la c'est pour la fonction de partages des données des points connus avec les robots
-    pub fn share_data(&self, robot: &mut Robot) {
-        for tile in &self.known_tiles {
-            robot.mark_tile_as_known(tile.x, tile.y);
-        }
+    // la station doit pouvoir créer des robots, le robot est ajouté à la flotte ici et nulle part ailleurs
+    pub fn create_robot(&mut self, id: usize, position: (usize, usize), module: Module, behavior: Behavior) -> &Robot {
+        let energy = module.battery_capacity();
+        self.robots.push(Robot::new(id, position, energy, module, behavior));
+        self.robots.last().unwrap()
     }
 
     // La on ajoute de l'energie à la station
     pub fn add_energy(&mut self, amount: u32) {
         self.energy += amount;
     }
-}
\ No newline at end of file
+
+    pub fn add_ore(&mut self, amount: u32) {
+        self.ore += amount;
+    }
+
+    // le robot vide sa soute dans les stocks de la station
+    pub fn unload_cargo(&mut self, robot: &mut Robot) {
+        for resource in robot.cargo.drain(..) {
+            match resource {
+                Resource::Energy => self.energy += ENERGY_PER_DELIVERY,
+                Resource::Ore => self.ore += 1,
+                Resource::PlaceOfInterest => {}
+            }
+        }
+    }
+
+    // les rapports du robot sont archivés, un même point d'intérêt ne rapporte des points qu'une fois
+    // renvoie le score gagné
+    pub fn receive_reports(&mut self, robot: &mut Robot) -> u32 {
+        let mut gained = 0;
+        for report in robot.reports.drain(..) {
+            if !self.science_reports.iter().any(|known| known.location == report.location) {
+                gained += report.score();
+            }
+            self.science_reports.push(report);
+        }
+        self.science_score += gained;
+        gained
+    }
+}
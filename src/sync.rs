@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::knowledge::KnownTile;
+use crate::robot::Robot;
+use crate::station::Station;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct SyncConfig {
+    // nombre max de tuiles par tick dans chaque sens, None = illimité
+    pub upload_per_tick: Option<usize>,
+    pub download_per_tick: Option<usize>,
+}
+
+// où en est la synchro d'un robot avec la station
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SyncState {
+    // dernière observation envoyée, triée par (tick, indice de la tuile)
+    pub uploaded: Option<(u64, usize)>,
+    // dernière révision de la base de la station reçue
+    pub downloaded: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TileUpdate {
+    pub position: (usize, usize),
+    pub tile: KnownTile,
+}
+
+// tout ce qui passe entre un robot et la station, pour mesurer comment l'information circule
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SyncMessage {
+    Upload { robot_id: usize, tick: u64, tiles: Vec<TileUpdate> },
+    Download { robot_id: usize, tick: u64, tiles: Vec<TileUpdate> },
+}
+
+impl SyncMessage {
+    pub fn tiles(&self) -> &[TileUpdate] {
+        match self {
+            SyncMessage::Upload { tiles, .. } | SyncMessage::Download { tiles, .. } => tiles,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SyncStats {
+    pub messages: u64,
+    pub uploaded_tiles: u64,
+    pub downloaded_tiles: u64,
+}
+
+// les observations du robot pas encore envoyées, au plus limit, renvoie aussi si tout est parti
+pub fn pending_upload(robot: &mut Robot, limit: Option<usize>, tick: u64) -> (Vec<TileUpdate>, bool) {
+    let belief = &robot.belief;
+    let mut pending: Vec<_> = belief
+        .tiles
+        .iter()
+        .enumerate()
+        .filter_map(|(index, observation)| observation.map(|observation| ((observation.tick, index), observation)))
+        .filter(|(key, _)| robot.sync.uploaded.is_none_or(|cursor| *key > cursor))
+        .collect();
+    pending.sort_by_key(|(key, _)| *key);
+
+    let complete = limit.is_none_or(|limit| pending.len() <= limit);
+    pending.truncate(limit.unwrap_or(usize::MAX));
+
+    let tiles = pending
+        .iter()
+        .map(|((_, index), observation)| TileUpdate {
+            position: (index % belief.width, index / belief.width),
+            tile: KnownTile { content: observation.content, observer: robot.id, tick: observation.tick },
+        })
+        .collect();
+
+    // tout ce qui a été vu jusqu'à ce tick est parti, sinon on reprendra après la dernière tuile envoyée
+    robot.sync.uploaded = if complete { Some((tick, usize::MAX)) } else { pending.last().map(|(key, _)| *key).or(robot.sync.uploaded) };
+    (tiles, complete)
+}
+
+// un robot à quai envoie ses nouvelles observations, puis la station lui renvoie ce qu'il ne sait pas encore
+// le téléchargement attend que l'envoi soit fini, sinon le robot renverrait des tuiles qu'il vient de recevoir
+pub fn sync(station: &mut Station, robot: &mut Robot, config: &SyncConfig, tick: u64) -> Vec<SyncMessage> {
+    let mut messages = vec![];
+    if !station.known_tiles.accepts(robot.belief.width, robot.belief.height) {
+        return messages;
+    }
+
+    let (uploaded, complete) = pending_upload(robot, config.upload_per_tick, tick);
+    if !uploaded.is_empty() {
+        for update in &uploaded {
+            station.known_tiles.merge(update.position.0, update.position.1, update.tile);
+        }
+        station.sync_stats.uploaded_tiles += uploaded.len() as u64;
+        messages.push(SyncMessage::Upload { robot_id: robot.id, tick, tiles: uploaded });
+    }
+    if !complete {
+        station.sync_stats.messages += messages.len() as u64;
+        return messages;
+    }
+
+    // inutile de renvoyer au robot ses propres observations
+    let mut changes: Vec<_> = station
+        .known_tiles
+        .changes_since(robot.sync.downloaded)
+        .into_iter()
+        .filter(|(_, _, tile)| tile.observer != robot.id)
+        .collect();
+    let complete = config.download_per_tick.is_none_or(|limit| changes.len() <= limit);
+    changes.truncate(config.download_per_tick.unwrap_or(usize::MAX));
+    robot.sync.downloaded = if complete {
+        station.known_tiles.revision
+    } else {
+        changes.last().map_or(robot.sync.downloaded, |(revision, ..)| *revision)
+    };
+
+    if !changes.is_empty() {
+        let mut downloaded = vec![];
+        for (_, (x, y), tile) in changes {
+            robot.receive_observation(x, y, tile.content, tile.tick);
+            downloaded.push(TileUpdate { position: (x, y), tile });
+        }
+        station.sync_stats.downloaded_tiles += downloaded.len() as u64;
+        messages.push(SyncMessage::Download { robot_id: robot.id, tick, tiles: downloaded });
+    }
+
+    station.sync_stats.messages += messages.len() as u64;
+    messages
+}
@@ -0,0 +1,571 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::belief::STALE_AFTER;
+use crate::comms::DistressCall;
+use crate::knowledge::KnowledgeBase;
+use crate::robot::{Behavior, Module, Robot};
+use crate::station::Station;
+use crate::tile::{Resource, TileContent};
+
+// la carte est découpée en carrés de cette taille pour les tâches d'exploration
+pub const REGION_SIZE: usize = 8;
+// une région est considérée explorée quand la station connaît cette part de ses tuiles
+pub const REGION_EXPLORED_RATIO: f64 = 0.5;
+// au-delà, une tâche pas terminée est retirée au robot
+pub const TASK_TIMEOUT: u64 = 200;
+// tâches de patrouille ouvertes en même temps au plus
+pub const MAX_PATROL_TASKS: usize = 2;
+// gisements proposés en même temps au plus, les plus proches de la station d'abord.
+// Sans limite une grande carte connue donne des milliers de tâches à comparer à chaque tick
+pub const MAX_OPEN_MINE_TASKS: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    // aller jusqu'au centre de la région en regardant autour
+    ExploreRegion((usize, usize)),
+    MineAt((usize, usize)),
+    AnalysePoi((usize, usize)),
+    // revoir une tuile que la station n'a pas vue depuis plus de STALE_AFTER ticks
+    Patrol((usize, usize)),
+    // aller donner de l'énergie au robot en panne sur cette tuile
+    Rescue((usize, usize)),
+    ReturnCargo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Task {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub assigned: Option<usize>,
+    pub assigned_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Allocation {
+    // la paire robot/tâche la plus proche d'abord, et on recommence
+    #[default]
+    GreedyNearest,
+    // affectation qui minimise la distance totale (algorithme hongrois)
+    Hungarian,
+    // chaque tâche est mise aux enchères, le robot qui annonce le plus petit coût la gagne
+    Auction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct TaskBoard {
+    pub tasks: Vec<Task>,
+    pub allocation: Allocation,
+    pub next_id: u64,
+    pub completed: u64,
+    pub cancelled: u64,
+    // les tuiles où la carte partagée montre une ressource, tenues à jour d'après ses révisions
+    // pour ne pas reparcourir toute la carte à chaque tick
+    pub sites: BTreeSet<(usize, usize)>,
+    pub sites_revision: u64,
+}
+
+impl TaskKind {
+    pub fn target(&self, station: (usize, usize)) -> (usize, usize) {
+        match *self {
+            TaskKind::ExploreRegion(position) | TaskKind::MineAt(position) | TaskKind::AnalysePoi(position) | TaskKind::Patrol(position) | TaskKind::Rescue(position) => position,
+            TaskKind::ReturnCargo => station,
+        }
+    }
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> u64 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u64
+}
+
+// seuls les comportements intégrés savent suivre une tâche
+fn can_do(robot: &Robot, kind: &TaskKind) -> bool {
+    if matches!(robot.behavior, Behavior::Custom(_)) || robot.energy == 0 || robot.stranded {
+        return false;
+    }
+    // la station ne sait que ce qu'on lui a rapporté, le robot peut en savoir plus
+    let believed = |(x, y): (usize, usize)| robot.belief.content_at(x, y);
+    match *kind {
+        TaskKind::ExploreRegion(centre) => {
+            robot.has_module(Module::Imaging) && region_needs_exploring(centre, robot.belief.width, robot.belief.height, |x, y| believed((x, y)).is_some())
+        }
+        TaskKind::MineAt(position) => {
+            robot.has_module(Module::Mining)
+                && !robot.is_cargo_full()
+                && believed(position).is_none_or(|content| matches!(content, TileContent::Resource(resource) if resource.is_minable()))
+        }
+        TaskKind::AnalysePoi(position) => {
+            robot.has_module(Module::Analysis)
+                && !robot.analyzed_sites.contains(&position)
+                && believed(position).is_none_or(|content| content == TileContent::Resource(Resource::PlaceOfInterest))
+        }
+        TaskKind::Patrol(position) => {
+            (robot.has_module(Module::Imaging) || robot.behavior == Behavior::Patrol) && believed(position) != Some(TileContent::Obstacle)
+        }
+        // assez d'énergie pour l'aller et pour en donner autant, sans être soi-même en panne
+        TaskKind::Rescue(position) => robot.comms.distress_sent.is_none() && robot.energy as u64 > 2 * manhattan(robot.position, position),
+        TaskKind::ReturnCargo => false,
+    }
+}
+
+// vrai si on connaît moins de REGION_EXPLORED_RATIO des tuiles de la région qui contient centre
+fn region_needs_exploring<F: Fn(usize, usize) -> bool>(centre: (usize, usize), width: usize, height: usize, is_known: F) -> bool {
+    let region_x = centre.0 / REGION_SIZE * REGION_SIZE;
+    let region_y = centre.1 / REGION_SIZE * REGION_SIZE;
+    let mut total = 0;
+    let mut known = 0;
+    for y in region_y..(region_y + REGION_SIZE).min(height) {
+        for x in region_x..(region_x + REGION_SIZE).min(width) {
+            total += 1;
+            if is_known(x, y) {
+                known += 1;
+            }
+        }
+    }
+    total > 0 && (known as f64) < total as f64 * REGION_EXPLORED_RATIO
+}
+
+fn station_needs_exploring(knowledge: &KnowledgeBase, centre: (usize, usize)) -> bool {
+    region_needs_exploring(centre, knowledge.width, knowledge.height, |x, y| knowledge.get(x, y).is_some())
+}
+
+impl Robot {
+    // vrai quand la tâche n'a plus lieu d'être, d'après ce que le robot sait
+    pub fn task_done(&self) -> bool {
+        let Some(task) = self.task else {
+            return false;
+        };
+        match task.kind {
+            TaskKind::ExploreRegion(position) => {
+                self.position == position || self.belief.content_at(position.0, position.1) == Some(TileContent::Obstacle)
+            }
+            TaskKind::MineAt(position) => self
+                .belief
+                .content_at(position.0, position.1)
+                .is_some_and(|content| !matches!(content, TileContent::Resource(resource) if resource.is_minable())),
+            TaskKind::AnalysePoi(position) => {
+                self.analyzed_sites.contains(&position)
+                    || self.belief.content_at(position.0, position.1).is_some_and(|content| content != TileContent::Resource(Resource::PlaceOfInterest))
+            }
+            // le robot l'a revue depuis qu'on lui a confié la tâche
+            TaskKind::Patrol(position) => self
+                .belief
+                .observation(position.0, position.1)
+                .is_some_and(|observation| observation.tick >= task.assigned_at || observation.content == TileContent::Obstacle),
+            // c'est la station qui sait si l'appel tient toujours
+            TaskKind::Rescue(_) => false,
+            TaskKind::ReturnCargo => self.cargo.is_empty(),
+        }
+    }
+}
+
+impl TaskBoard {
+    pub fn add(&mut self, kind: TaskKind) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(Task { id, kind, assigned: None, assigned_at: 0 });
+        id
+    }
+
+    // une tâche créée directement pour un robot, sans passer par l'allocation
+    pub fn add_assigned(&mut self, kind: TaskKind, robot_id: usize, tick: u64) -> Task {
+        let task = Task { id: self.next_id, kind, assigned: Some(robot_id), assigned_at: tick };
+        self.next_id += 1;
+        self.tasks.push(task);
+        task
+    }
+
+    pub fn contains(&self, kind: &TaskKind) -> bool {
+        self.tasks.iter().any(|task| task.kind == *kind)
+    }
+
+    pub fn complete(&mut self, id: u64) {
+        let before = self.tasks.len();
+        self.tasks.retain(|task| task.id != id);
+        self.completed += (before - self.tasks.len()) as u64;
+    }
+
+    pub fn cancel(&mut self, id: u64) {
+        let before = self.tasks.len();
+        self.tasks.retain(|task| task.id != id);
+        self.cancelled += (before - self.tasks.len()) as u64;
+    }
+
+    // la tâche retourne au tableau, un autre robot pourra la prendre
+    pub fn release(&mut self, id: u64) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.assigned = None;
+        }
+    }
+
+    pub fn unassigned(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|task| task.assigned.is_none())
+    }
+
+    // renvoie les paires (indice du robot, id de la tâche) choisies par la stratégie
+    pub fn allocate(&mut self, robots: &[Robot], station: (usize, usize), tick: u64) -> Vec<(usize, u64)> {
+        let free: Vec<usize> = (0..robots.len()).filter(|&index| robots[index].task.is_none()).collect();
+        let open: Vec<Task> = self.unassigned().copied().collect();
+        if free.is_empty() || open.is_empty() {
+            return vec![];
+        }
+        // coût de chaque paire, None si le robot ne sait pas faire la tâche
+        let costs: Vec<Vec<Option<u64>>> = free
+            .iter()
+            .map(|&index| {
+                let robot = &robots[index];
+                open.iter()
+                    .map(|task| can_do(robot, &task.kind).then(|| manhattan(robot.position, task.kind.target(station))))
+                    .collect()
+            })
+            .collect();
+
+        let pairs = match self.allocation {
+            Allocation::GreedyNearest => greedy(&costs),
+            Allocation::Hungarian => hungarian(&costs),
+            Allocation::Auction => auction(&costs),
+        };
+
+        let mut assignments = vec![];
+        for (row, column) in pairs {
+            let (robot_index, task_id) = (free[row], open[column].id);
+            if let Some(task) = self.tasks.iter_mut().find(|task| task.id == task_id) {
+                task.assigned = Some(robots[robot_index].id);
+                task.assigned_at = tick;
+            }
+            assignments.push((robot_index, task_id));
+        }
+        assignments
+    }
+}
+
+fn greedy(costs: &[Vec<Option<u64>>]) -> Vec<(usize, usize)> {
+    let mut candidates = vec![];
+    for (row, line) in costs.iter().enumerate() {
+        for (column, cost) in line.iter().enumerate() {
+            if let Some(cost) = cost {
+                candidates.push((*cost, row, column));
+            }
+        }
+    }
+    candidates.sort();
+
+    let mut rows_taken = vec![false; costs.len()];
+    let mut columns_taken = vec![false; costs.first().map_or(0, |line| line.len())];
+    let mut pairs = vec![];
+    for (_, row, column) in candidates {
+        if !rows_taken[row] && !columns_taken[column] {
+            rows_taken[row] = true;
+            columns_taken[column] = true;
+            pairs.push((row, column));
+        }
+    }
+    pairs
+}
+
+// contract-net simplifié : les tâches sont annoncées dans l'ordre, chaque robot libre enchérit son coût
+fn auction(costs: &[Vec<Option<u64>>]) -> Vec<(usize, usize)> {
+    let columns = costs.first().map_or(0, |line| line.len());
+    let mut rows_taken = vec![false; costs.len()];
+    let mut pairs = vec![];
+    for column in 0..columns {
+        let winner = (0..costs.len())
+            .filter(|&row| !rows_taken[row])
+            .filter_map(|row| costs[row][column].map(|bid| (bid, row)))
+            .min();
+        if let Some((_, row)) = winner {
+            rows_taken[row] = true;
+            pairs.push((row, column));
+        }
+    }
+    pairs
+}
+
+// algorithme hongrois avec potentiels, en O(n² m) pour n lignes <= m colonnes
+// les paires impossibles coûtent très cher et sont retirées à la fin
+fn hungarian(costs: &[Vec<Option<u64>>]) -> Vec<(usize, usize)> {
+    let rows = costs.len();
+    let columns = costs.first().map_or(0, |line| line.len());
+    if rows == 0 || columns == 0 {
+        return vec![];
+    }
+    let transposed = rows > columns;
+    let (n, m) = if transposed { (columns, rows) } else { (rows, columns) };
+    let impossible = 1 + costs.iter().flatten().flatten().sum::<u64>() as i64;
+    let cost = |i: usize, j: usize| -> i64 {
+        let value = if transposed { costs[j][i] } else { costs[i][j] };
+        value.map_or(impossible, |value| value as i64)
+    };
+
+    // indices à partir de 1, la colonne 0 sert de sentinelle
+    let mut u = vec![0i64; n + 1];
+    let mut v = vec![0i64; m + 1];
+    let mut matched = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+    for i in 1..=n {
+        matched[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![i64::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = matched[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let current = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                    if current < min_v[j] {
+                        min_v[j] = current;
+                        way[j] = j0;
+                    }
+                    if min_v[j] < delta {
+                        delta = min_v[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[matched[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if matched[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            matched[j0] = matched[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut pairs = vec![];
+    for (j, &i) in matched.iter().enumerate().skip(1) {
+        if i != 0 {
+            let (row, column) = if transposed { (j - 1, i - 1) } else { (i - 1, j - 1) };
+            if costs[row][column].is_some() {
+                pairs.push((row, column));
+            }
+        }
+    }
+    pairs.sort();
+    pairs
+}
+
+impl Station {
+    // met le tableau à jour d'après la carte partagée puis distribue les tâches aux robots libres
+    // les affectations partent par radio : pas besoin d'être à quai pour recevoir une tâche
+    pub fn plan_tasks(&mut self, tick: u64) {
+        let calls = self.distress_calls();
+        // tâches terminées, abandonnées ou interrompues par une soute pleine
+        for robot in &mut self.robots {
+            let Some(task) = robot.task else {
+                continue;
+            };
+            let rescued = matches!(task.kind, TaskKind::Rescue(position) if !calls.iter().any(|call| call.position == position));
+            if robot.task_done() || rescued {
+                self.task_board.complete(task.id);
+                robot.task = None;
+            } else if tick.saturating_sub(task.assigned_at) > TASK_TIMEOUT {
+                self.task_board.cancel(task.id);
+                robot.task = None;
+            } else if robot.is_cargo_full() && task.kind != TaskKind::ReturnCargo {
+                self.task_board.release(task.id);
+                robot.task = None;
+            }
+        }
+
+        self.post_tasks(&calls, tick);
+
+        // le retour à la station est donné directement au robot concerné
+        for robot in &mut self.robots {
+            if robot.task.is_none() && robot.is_cargo_full() && !matches!(robot.behavior, Behavior::Custom(_)) {
+                robot.task = Some(self.task_board.add_assigned(TaskKind::ReturnCargo, robot.id, tick));
+            }
+        }
+
+        for (robot_index, task_id) in self.task_board.allocate(&self.robots, self.position, tick) {
+            let task = self.task_board.tasks.iter().find(|task| task.id == task_id).copied();
+            self.robots[robot_index].task = task;
+        }
+    }
+
+    // les appels que la station entend : ceux des robots en panne et ceux que les autres ont captés, le plus récent par robot
+    pub fn distress_calls(&self) -> Vec<DistressCall> {
+        let own = self
+            .robots
+            .iter()
+            .filter_map(|robot| robot.comms.distress_sent.map(|tick| DistressCall { robot_id: robot.id, position: robot.position, tick }));
+        let heard = self.robots.iter().flat_map(|robot| robot.comms.distress_calls.iter().copied());
+        let mut calls: Vec<DistressCall> = vec![];
+        for call in own.chain(heard) {
+            match calls.iter_mut().find(|known| known.robot_id == call.robot_id) {
+                Some(known) if known.tick < call.tick => *known = call,
+                Some(_) => {}
+                None => calls.push(call),
+            }
+        }
+        calls
+    }
+
+    // un sauveteur arrivé sur la tuile du robot en panne ou à côté lui donne la moitié de son énergie.
+    // Renvoie les robots secourus
+    pub fn deliver_rescues(&mut self) -> Vec<usize> {
+        let mut rescued = vec![];
+        for index in 0..self.robots.len() {
+            let Some(task) = self.robots[index].task else {
+                continue;
+            };
+            let TaskKind::Rescue(position) = task.kind else {
+                continue;
+            };
+            if manhattan(self.robots[index].position, position) > 1 {
+                continue;
+            }
+            let stranded = self
+                .robots
+                .iter()
+                .position(|robot| robot.position == position && robot.comms.distress_sent.is_some() && robot.id != self.robots[index].id);
+            let gift = self.robots[index].energy / 2;
+            if let Some(stranded) = stranded.filter(|_| gift > 0) {
+                let capacity = self.robots[stranded].battery_capacity();
+                let given = gift.min(capacity.saturating_sub(self.robots[stranded].energy));
+                self.robots[index].energy -= given;
+                self.robots[stranded].energy += given;
+                // il repart, son prochain tick dira s'il peut vraiment bouger
+                self.robots[stranded].stranded = false;
+                rescued.push(self.robots[stranded].id);
+            }
+            self.task_board.complete(task.id);
+            self.robots[index].task = None;
+        }
+        rescued
+    }
+
+    // seules les tuiles modifiées depuis le dernier passage peuvent faire apparaître ou disparaître une ressource
+    fn update_sites(&mut self) {
+        let knowledge = &self.known_tiles;
+        let board = &mut self.task_board;
+        // la base a été remplacée, on repart de zéro
+        if knowledge.revision < board.sites_revision {
+            board.sites.clear();
+            board.sites_revision = 0;
+        }
+        if knowledge.revision == board.sites_revision {
+            return;
+        }
+        for (_, position, tile) in knowledge.changes_since(board.sites_revision) {
+            if matches!(tile.content, TileContent::Resource(_)) {
+                board.sites.insert(position);
+            } else {
+                board.sites.remove(&position);
+            }
+        }
+        board.sites_revision = knowledge.revision;
+    }
+
+    // ajoute les tâches que la carte partagée rend possibles, retire celles qui ne servent plus
+    fn post_tasks(&mut self, calls: &[DistressCall], tick: u64) {
+        self.update_sites();
+        let knowledge = &self.known_tiles;
+        let analyzed: HashSet<(usize, usize)> = self
+            .science_reports
+            .iter()
+            .map(|report| report.location)
+            .chain(self.robots.iter().flat_map(|robot| robot.analyzed_sites.iter().copied()))
+            .collect();
+
+        let still_useful = |kind: &TaskKind| match *kind {
+            TaskKind::MineAt((x, y)) => matches!(
+                knowledge.get(x, y).map(|tile| tile.content),
+                Some(TileContent::Resource(resource)) if resource.is_minable()
+            ),
+            TaskKind::AnalysePoi(position) => !analyzed.contains(&position),
+            TaskKind::ExploreRegion(position) => station_needs_exploring(knowledge, position),
+            TaskKind::Patrol((x, y)) => knowledge.get(x, y).is_some_and(|tile| tick.saturating_sub(tile.tick) > STALE_AFTER),
+            TaskKind::Rescue(position) => calls.iter().any(|call| call.position == position),
+            TaskKind::ReturnCargo => true,
+        };
+        let before = self.task_board.tasks.len();
+        self.task_board.tasks.retain(|task| task.assigned.is_some() || still_useful(&task.kind));
+        self.task_board.cancelled += (before - self.task_board.tasks.len()) as u64;
+
+        let mut on_board: HashSet<TaskKind> = self.task_board.tasks.iter().map(|task| task.kind).collect();
+        let mut new_tasks: Vec<_> = calls.iter().map(|call| TaskKind::Rescue(call.position)).collect();
+        let open_mines = self.task_board.unassigned().filter(|task| matches!(task.kind, TaskKind::MineAt(_))).count();
+        let mut deposits = vec![];
+        for &(x, y) in &self.task_board.sites {
+            match knowledge.get(x, y).map(|tile| tile.content) {
+                Some(TileContent::Resource(resource))
+                    if resource.is_minable() && open_mines < MAX_OPEN_MINE_TASKS && !on_board.contains(&TaskKind::MineAt((x, y))) =>
+                {
+                    deposits.push((manhattan(self.position, (x, y)), (x, y)));
+                }
+                Some(TileContent::Resource(Resource::PlaceOfInterest)) if !analyzed.contains(&(x, y)) => {
+                    new_tasks.push(TaskKind::AnalysePoi((x, y)))
+                }
+                _ => {}
+            }
+        }
+        deposits.sort_unstable();
+        new_tasks.extend(
+            deposits
+                .into_iter()
+                .take(MAX_OPEN_MINE_TASKS.saturating_sub(open_mines))
+                .map(|(_, position)| TaskKind::MineAt(position)),
+        );
+        for region_y in (0..knowledge.height).step_by(REGION_SIZE) {
+            for region_x in (0..knowledge.width).step_by(REGION_SIZE) {
+                let centre = (
+                    (region_x + REGION_SIZE / 2).min(knowledge.width - 1),
+                    (region_y + REGION_SIZE / 2).min(knowledge.height - 1),
+                );
+                let known_obstacle = knowledge.get(centre.0, centre.1).is_some_and(|tile| tile.content == TileContent::Obstacle);
+                if !known_obstacle && station_needs_exploring(knowledge, centre) {
+                    new_tasks.push(TaskKind::ExploreRegion(centre));
+                }
+            }
+        }
+
+        // une patrouille par région bien connue dont la tuile la plus ancienne est périmée, les plus vieilles d'abord
+        let mut stale_regions = vec![];
+        for region_y in (0..knowledge.height).step_by(REGION_SIZE) {
+            for region_x in (0..knowledge.width).step_by(REGION_SIZE) {
+                let stalest = (region_y..(region_y + REGION_SIZE).min(knowledge.height))
+                    .flat_map(|y| (region_x..(region_x + REGION_SIZE).min(knowledge.width)).map(move |x| (x, y)))
+                    .filter_map(|(x, y)| knowledge.get(x, y).filter(|tile| tile.content != TileContent::Obstacle).map(|tile| (tile.tick, (x, y))))
+                    .min();
+                if let Some((seen, position)) = stalest {
+                    if tick.saturating_sub(seen) > STALE_AFTER && !station_needs_exploring(knowledge, position) {
+                        stale_regions.push((seen, position));
+                    }
+                }
+            }
+        }
+        stale_regions.sort();
+        let patrols = self.task_board.tasks.iter().filter(|task| matches!(task.kind, TaskKind::Patrol(_))).count();
+        new_tasks.extend(
+            stale_regions
+                .into_iter()
+                .take(MAX_PATROL_TASKS.saturating_sub(patrols))
+                .map(|(_, position)| TaskKind::Patrol(position)),
+        );
+
+        for kind in new_tasks {
+            if on_board.insert(kind) {
+                self.task_board.add(kind);
+            }
+        }
+    }
+}
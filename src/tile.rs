@@ -1,5 +1,3 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use serde::{Deserialize, Serialize};
 use rand::Rng;
 
@@ -19,6 +17,11 @@ impl Resource {
             _ => Resource::PlaceOfInterest,
         }
     }
+
+    // les lieux d'intérêt s'analysent sur place, ils ne se transportent pas
+    pub fn is_minable(&self) -> bool {
+        matches!(self, Resource::Energy | Resource::Ore)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -32,7 +35,16 @@ pub enum TileContent {
 pub struct Tile {
     pub explored: bool,
     pub content: TileContent,
+    // tick logique de la dernière observation, pas une heure système
     pub timestamp: u64,
+    // unités qu'il reste dans le gisement, la tuile redevient vide quand on a tout extrait
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+}
+
+// les cartes d'avant les quantités : une ressource = une unité
+fn default_quantity() -> u32 {
+    1
 }
 
 impl Tile {
@@ -40,7 +52,15 @@ impl Tile {
         Tile {
             explored,
             content,
-            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            timestamp: 0,
+            quantity: default_quantity(),
+        }
+    }
+
+    pub fn deposit(resource: Resource, quantity: u32) -> Self {
+        Tile {
+            quantity: quantity.max(1),
+            ..Tile::new(false, TileContent::Resource(resource))
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,198 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::belief::BeliefMap;
+use crate::map::Map;
+use crate::pathfinding::{self, CostMap};
+use crate::robot::Robot;
+use crate::tile::TileContent;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct TrafficConfig {
+    // désactivé par défaut : les robots se traversent comme avant
+    pub enabled: bool,
+    // ticks d'attente derrière un robot avant de chercher un détour
+    pub patience: u32,
+}
+
+impl Default for TrafficConfig {
+    fn default() -> Self {
+        Self { enabled: false, patience: 2 }
+    }
+}
+
+// ce qu'un robot a subi à cause des autres
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrafficState {
+    // ticks consécutifs bloqué derrière un autre robot
+    pub waiting: u32,
+    pub waits: u64,
+    pub detours: u64,
+    pub yields: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrafficStats {
+    pub waits: u64,
+    pub detours: u64,
+    pub yields: u64,
+}
+
+// qui occupe chaque tuile pendant le tick en cours. Les robots jouent dans l'ordre de la flotte :
+// celui qui a déjà bougé réserve sa nouvelle tuile, les suivants doivent faire avec
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReservationTable {
+    pub width: usize,
+    pub height: usize,
+    // la station accueille autant de robots qu'on veut
+    pub station: (usize, usize),
+    pub patience: u32,
+    cells: Vec<Option<usize>>,
+    // le reste du chemin de chaque robot, pour savoir où s'écarter
+    plans: Vec<(usize, Vec<(usize, usize)>)>,
+}
+
+impl ReservationTable {
+    pub fn new(width: usize, height: usize, station: (usize, usize), patience: u32) -> Self {
+        Self {
+            width,
+            height,
+            station,
+            patience,
+            cells: vec![None; width * height],
+            plans: vec![],
+        }
+    }
+
+    pub fn from_robots(map: &Map, station: (usize, usize), patience: u32, robots: &[Robot]) -> Self {
+        let mut table = Self::new(map.width, map.height, station, patience);
+        for robot in robots {
+            table.reserve(robot.id, robot.position);
+            if let Some(route) = robot.route.as_ref().filter(|route| route.expected_position() == robot.position) {
+                table.plan(robot.id, route.remaining().to_vec());
+            }
+        }
+        table
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> Option<usize> {
+        (x < self.width && y < self.height && (x, y) != self.station).then_some(y * self.width + x)
+    }
+
+    pub fn holder(&self, position: (usize, usize)) -> Option<usize> {
+        self.index(position).and_then(|index| self.cells[index])
+    }
+
+    // le robot qui empêche robot_id d'entrer sur cette tuile
+    pub fn blocker(&self, position: (usize, usize), robot_id: usize) -> Option<usize> {
+        self.holder(position).filter(|&holder| holder != robot_id)
+    }
+
+    pub fn is_free_for(&self, position: (usize, usize), robot_id: usize) -> bool {
+        self.blocker(position, robot_id).is_none()
+    }
+
+    pub fn reserve(&mut self, robot_id: usize, position: (usize, usize)) {
+        if let Some(index) = self.index(position) {
+            self.cells[index] = Some(robot_id);
+        }
+    }
+
+    pub fn move_robot(&mut self, robot_id: usize, from: (usize, usize), to: (usize, usize)) {
+        if let Some(index) = self.index(from) {
+            if self.cells[index] == Some(robot_id) {
+                self.cells[index] = None;
+            }
+        }
+        self.reserve(robot_id, to);
+    }
+
+    pub fn plan(&mut self, robot_id: usize, steps: Vec<(usize, usize)>) {
+        self.plans.retain(|(id, _)| *id != robot_id);
+        if !steps.is_empty() {
+            self.plans.push((robot_id, steps));
+        }
+    }
+
+    pub fn is_planned_by(&self, position: (usize, usize), robot_id: usize) -> bool {
+        self.plans.iter().any(|(id, steps)| *id == robot_id && steps.contains(&position))
+    }
+}
+
+// la croyance du robot où les tuiles occupées par les autres sont infranchissables, pour chercher un détour
+pub struct AvoidRobots<'a> {
+    pub belief: &'a BeliefMap,
+    pub reservations: &'a ReservationTable,
+    pub robot_id: usize,
+}
+
+impl CostMap for AvoidRobots<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        self.belief.dimensions()
+    }
+
+    fn cost_at(&self, position: (usize, usize)) -> Option<u32> {
+        if !self.reservations.is_free_for(position, self.robot_id) {
+            return None;
+        }
+        self.belief.cost_at(position)
+    }
+}
+
+impl Robot {
+    // le prochain pas est pris par `blocker` : on attend un peu, puis on cherche un détour autour des robots.
+    // S'il n'y en a pas (couloir), celui qui a une niche hors du chemin de l'autre s'y range.
+    // Sinon le moins prioritaire des deux (l'id le plus grand) recule jusqu'à en trouver une,
+    // après une seconde attente pour laisser à l'autre le temps de se ranger s'il peut
+    pub fn give_way<R: Rng + ?Sized>(
+        &mut self,
+        map: &Map,
+        reservations: &ReservationTable,
+        goal: (usize, usize),
+        blocker: usize,
+        rng: &mut R,
+    ) {
+        self.traffic.waiting += 1;
+        self.traffic.waits += 1;
+        if self.traffic.waiting <= reservations.patience {
+            return;
+        }
+
+        let avoid = AvoidRobots { belief: &self.belief, reservations, robot_id: self.id };
+        if let Some(mut route) = pathfinding::find_path(&avoid, self.position, goal) {
+            if let Some(next_step) = route.peek() {
+                // le détour passe par la croyance du robot, le pas est vérifié sur la vraie carte
+                if map.tile_at(next_step.0, next_step.1).is_none_or(|tile| tile.content == TileContent::Obstacle) {
+                    self.route = None;
+                    return;
+                }
+                route.advance();
+                self.position = next_step;
+                self.route = Some(route);
+                self.traffic.waiting = 0;
+                self.traffic.detours += 1;
+                return;
+            }
+        }
+
+        let free: Vec<_> = [(0, 1), (1, 0), (0, -1), (-1, 0)]
+            .into_iter()
+            .filter_map(|(dx, dy)| Some((self.position.0.checked_add_signed(dx)?, self.position.1.checked_add_signed(dy)?)))
+            .filter(|&(x, y)| map.tile_at(x, y).is_some_and(|tile| tile.content != TileContent::Obstacle))
+            .filter(|&position| reservations.is_free_for(position, self.id))
+            .collect();
+        let aside: Vec<_> = free.iter().copied().filter(|&position| !reservations.is_planned_by(position, blocker)).collect();
+        let choices = match (aside.is_empty(), self.id > blocker) {
+            (false, _) => aside,
+            (true, true) if self.traffic.waiting > 2 * reservations.patience => free,
+            (true, _) => return,
+        };
+        if choices.is_empty() {
+            return;
+        }
+        self.position = choices[rng.gen_range(0..choices.len())];
+        self.route = None;
+        self.traffic.yields += 1;
+    }
+}
@@ -0,0 +1,155 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::map::Map;
+use crate::tile::{Resource, TileContent};
+
+// portée du capteur d'un robot pris dans une tempête : il ne voit plus que sa propre tuile
+pub const STORM_SENSOR_RADIUS: usize = 0;
+
+// les chances sont tirées à chaque tick avec le rng de la simulation
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct WorldEventsConfig {
+    // désactivé par défaut : la carte ne change que quand les robots minent
+    pub enabled: bool,
+    // chance par tick qu'un gisement d'énergie entamé (ou épuisé) regagne une unité
+    pub energy_regen_chance: f64,
+    pub max_energy_deposit: u32,
+    // chance par tick qu'un point d'intérêt apparaisse sur une tuile vide
+    pub new_poi_chance: f64,
+    pub dust_storm_chance: f64,
+    pub dust_storm_radius: usize,
+    pub dust_storm_duration: u64,
+    // un éboulement bouche jusqu'à rockslide_size tuiles vides voisines
+    pub rockslide_chance: f64,
+    pub rockslide_size: usize,
+}
+
+impl Default for WorldEventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            energy_regen_chance: 0.01,
+            max_energy_deposit: 4,
+            new_poi_chance: 0.005,
+            dust_storm_chance: 0.003,
+            dust_storm_radius: 4,
+            dust_storm_duration: 40,
+            rockslide_chance: 0.002,
+            rockslide_size: 3,
+        }
+    }
+}
+
+// les capteurs sont aveugles dans le disque jusqu'au tick `until`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DustStorm {
+    pub centre: (usize, usize),
+    pub radius: usize,
+    pub until: u64,
+}
+
+impl DustStorm {
+    pub fn covers(&self, (x, y): (usize, usize)) -> bool {
+        let dx = x.abs_diff(self.centre.0);
+        let dy = y.abs_diff(self.centre.1);
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct WorldEventStats {
+    pub regenerated: u64,
+    pub new_points_of_interest: u64,
+    pub dust_storms: u64,
+    pub rockslides: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WorldEvents {
+    pub stats: WorldEventStats,
+}
+
+impl WorldEvents {
+    // un tick d'évènements. `occupied` : station et robots, on n'y fait rien apparaître
+    pub fn update<R: Rng + ?Sized>(&mut self, map: &mut Map, config: &WorldEventsConfig, occupied: &[(usize, usize)], rng: &mut R, tick: u64) {
+        map.storms.retain(|storm| storm.until > tick);
+
+        self.regenerate_energy(map, config, rng);
+
+        if config.new_poi_chance > 0.0 && rng.gen_bool(config.new_poi_chance.min(1.0)) {
+            if let Some((x, y)) = random_free_tile(map, occupied, rng) {
+                map.throw_resource_at(x, y, Resource::PlaceOfInterest);
+                self.stats.new_points_of_interest += 1;
+            }
+        }
+
+        if config.dust_storm_chance > 0.0 && rng.gen_bool(config.dust_storm_chance.min(1.0)) {
+            let centre = (rng.gen_range(0..map.width), rng.gen_range(0..map.height));
+            map.storms.push(DustStorm { centre, radius: config.dust_storm_radius, until: tick + config.dust_storm_duration });
+            self.stats.dust_storms += 1;
+        }
+
+        if config.rockslide_chance > 0.0 && rng.gen_bool(config.rockslide_chance.min(1.0)) {
+            if let Some(start) = random_free_tile(map, occupied, rng) {
+                self.rockslide(map, config, occupied, start, rng);
+            }
+        }
+    }
+
+    // les sites sont notés par la carte (voir Map::energy_sites), même ceux vidés avant le premier tick
+    fn regenerate_energy<R: Rng + ?Sized>(&mut self, map: &mut Map, config: &WorldEventsConfig, rng: &mut R) {
+        if config.energy_regen_chance <= 0.0 {
+            return;
+        }
+        let sites: Vec<_> = map.energy_sites.iter().copied().collect();
+        for (x, y) in sites {
+            let quantity = match map.deposit_at(x, y) {
+                Some((Resource::Energy, quantity)) if quantity < config.max_energy_deposit => quantity,
+                None if map.is_empty(x, y) => 0,
+                _ => continue,
+            };
+            if rng.gen_bool(config.energy_regen_chance.min(1.0)) {
+                map.throw_deposit_at(x, y, Resource::Energy, quantity + 1);
+                self.stats.regenerated += 1;
+            }
+        }
+    }
+
+    // des rochers tombent sur la tuile de départ puis sur des voisines vides, au hasard
+    fn rockslide<R: Rng + ?Sized>(&mut self, map: &mut Map, config: &WorldEventsConfig, occupied: &[(usize, usize)], start: (usize, usize), rng: &mut R) {
+        let mut buried = vec![start];
+        let mut current = start;
+        while buried.len() < config.rockslide_size.max(1) {
+            let candidates: Vec<_> = [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                .into_iter()
+                .filter_map(|(dx, dy)| Some((current.0.checked_add_signed(dx)?, current.1.checked_add_signed(dy)?)))
+                .filter(|&(x, y)| map.is_empty(x, y) && !occupied.contains(&(x, y)) && !buried.contains(&(x, y)))
+                .collect();
+            if candidates.is_empty() {
+                break;
+            }
+            current = candidates[rng.gen_range(0..candidates.len())];
+            buried.push(current);
+        }
+        for (x, y) in buried {
+            if let Some(tile) = map.tile_at_mut(x, y) {
+                tile.content = TileContent::Obstacle;
+            }
+        }
+        self.stats.rockslides += 1;
+    }
+}
+
+// une tuile vide tirée au hasard, sans robot ni station dessus
+fn random_free_tile<R: Rng + ?Sized>(map: &Map, occupied: &[(usize, usize)], rng: &mut R) -> Option<(usize, usize)> {
+    let free: Vec<_> = (0..map.height)
+        .flat_map(|y| (0..map.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.is_empty(x, y) && !occupied.contains(&(x, y)))
+        .collect();
+    if free.is_empty() {
+        return None;
+    }
+    Some(free[rng.gen_range(0..free.len())])
+}
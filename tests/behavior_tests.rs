@@ -0,0 +1,62 @@
+use eerea::behavior::{Action, BehaviorRegistry, CollectionBehavior, RobotBehavior, WorldView};
+use eerea::energy::EnergyModel;
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::tile::Resource;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// reste toujours sur place
+struct StayPut;
+
+impl RobotBehavior for StayPut {
+    fn decide(&self, _robot: &Robot, _world: &WorldView, _rng: &mut dyn RngCore) -> Action {
+        Action::Idle
+    }
+}
+
+#[test]
+fn test_collection_behavior_mines_when_on_resource() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(2, 2, Resource::Energy);
+    let mut robot = Robot::new(1, (2, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    let world = WorldView { station_position: (5, 5), tick: 0 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    // tant qu'il n'a rien vu, il ne sait pas qu'il est sur un gisement
+    assert_ne!(CollectionBehavior.decide(&robot, &world, &mut rng), Action::Mine);
+    robot.sense(&mut map, 0);
+    assert_eq!(CollectionBehavior.decide(&robot, &world, &mut rng), Action::Mine);
+}
+
+#[test]
+fn test_collection_behavior_targets_known_resource() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(3, 2, Resource::Ore);
+    let mut robot = Robot::new(1, (2, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    robot.sense(&mut map, 0);
+    let world = WorldView { station_position: (5, 5), tick: 0 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    assert_eq!(CollectionBehavior.decide(&robot, &world, &mut rng), Action::MoveTowards((3, 2)));
+}
+
+#[test]
+fn test_registry_resolves_custom_behavior() {
+    let mut registry = BehaviorRegistry::default();
+    registry.register("stay", StayPut);
+    assert!(registry.resolve(&Behavior::Custom("stay".to_string())).is_some());
+    assert!(registry.resolve(&Behavior::Custom("unknown".to_string())).is_none());
+    assert!(registry.resolve(&Behavior::Exploration).is_some());
+}
+
+#[test]
+fn test_simulation_uses_registered_behavior() {
+    let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    simulation.behaviors.register("stay", StayPut);
+    let start = (5, 5);
+    simulation.station.robots.push(Robot::new(10, start, 100, Module::Imaging, Behavior::Custom("stay".to_string())));
+    simulation.run(10);
+    let robot = simulation.station.robots.iter().find(|r| r.id == 10).unwrap();
+    assert_eq!(robot.position, start);
+    assert_eq!(robot.energy, 100 - 10 * EnergyModel::default().idle_cost);
+}
@@ -0,0 +1,51 @@
+use eerea::belief::{BeliefMap, TileState, STALE_AFTER};
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::tile::{Resource, TileContent};
+
+#[test]
+fn test_belief_starts_unknown() {
+    let belief = BeliefMap::new(5, 5);
+    assert_eq!(belief.state(2, 2, 0), TileState::Unknown);
+    assert_eq!(belief.known_count(), 0);
+}
+
+#[test]
+fn test_belief_goes_stale() {
+    let mut belief = BeliefMap::new(5, 5);
+    belief.observe(1, 1, TileContent::Obstacle, 10);
+    assert_eq!(belief.state(1, 1, 10 + STALE_AFTER), TileState::Seen(TileContent::Obstacle));
+    assert_eq!(belief.state(1, 1, 11 + STALE_AFTER), TileState::Stale(TileContent::Obstacle));
+}
+
+#[test]
+fn test_belief_nearest_resource() {
+    let mut belief = BeliefMap::new(10, 10);
+    belief.observe(8, 8, TileContent::Resource(Resource::Ore), 0);
+    belief.observe(2, 3, TileContent::Resource(Resource::Ore), 0);
+    belief.observe(1, 1, TileContent::Resource(Resource::PlaceOfInterest), 0);
+    assert_eq!(belief.nearest_resource((1, 2), |_, r| r.is_minable()), Some((2, 3)));
+    assert_eq!(belief.nearest_resource((9, 9), |_, r| r == Resource::PlaceOfInterest), Some((1, 1)));
+}
+
+#[test]
+fn test_robot_only_knows_what_it_sensed() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(8, 8, Resource::Energy);
+    let mut robot = Robot::new(1, (2, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    robot.sense(&mut map, 3);
+    assert_eq!(robot.belief.known_count(), 5);
+    assert_eq!(robot.belief.observation(2, 3).unwrap().tick, 3);
+    assert_eq!(robot.belief.state(8, 8, 3), TileState::Unknown);
+    assert_eq!(robot.find_resource(), None);
+}
+
+#[test]
+fn test_big_deposit_further_away_beats_small_one_nearby() {
+    let mut belief = BeliefMap::new(20, 20);
+    belief.observe_deposit(2, 0, TileContent::Resource(Resource::Ore), 1, 0);
+    belief.observe_deposit(8, 0, TileContent::Resource(Resource::Ore), 5, 0);
+    assert_eq!(belief.best_deposit((0, 0), 5, |_, _| true), Some((8, 0)));
+    // plus de place que pour une unité : autant prendre la plus proche
+    assert_eq!(belief.best_deposit((0, 0), 1, |_, _| true), Some((2, 0)));
+}
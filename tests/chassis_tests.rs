@@ -0,0 +1,111 @@
+use eerea::chassis::{RefitError, RefitOrder};
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot, DEFAULT_CARGO_CAPACITY};
+use eerea::sensing::IMAGING_SENSOR_RADIUS;
+use eerea::simulation::Simulation;
+use eerea::station::Station;
+
+fn miner_at(position: (usize, usize)) -> Robot {
+    Robot::new(1, position, 50, Module::Mining, Behavior::ResourceCollection)
+}
+
+#[test]
+fn test_modules_add_capabilities() {
+    let mut robot = miner_at((0, 0));
+    robot.install_module(Module::Imaging).unwrap();
+    robot.install_module(Module::CargoBay).unwrap();
+
+    assert!(robot.has_module(Module::Mining));
+    assert_eq!(robot.field_of_view().radius, IMAGING_SENSOR_RADIUS);
+    assert_eq!(robot.cargo_capacity, 2 * DEFAULT_CARGO_CAPACITY);
+    assert_eq!(robot.mining_rate(), 1);
+    assert_eq!(
+        robot.battery_capacity(),
+        Module::Mining.battery_capacity() + Module::Imaging.battery_capacity()
+    );
+}
+
+#[test]
+fn test_chassis_limits_slots_and_mass() {
+    let mut robot = miner_at((0, 0));
+    // 5 + 4 + 2 > 10
+    robot.install_module(Module::Analysis).unwrap();
+    assert!(matches!(robot.install_module(Module::Imaging), Err(RefitError::TooHeavy { mass: 11, .. })));
+
+    let mut scout = Robot::new(2, (0, 0), 50, Module::Imaging, Behavior::Exploration);
+    scout.chassis.max_mass = 100;
+    scout.install_module(Module::Imaging).unwrap();
+    scout.install_module(Module::Imaging).unwrap();
+    assert_eq!(scout.install_module(Module::Imaging), Err(RefitError::NoFreeSlot { slots: 3 }));
+    assert_eq!(scout.modules.len(), 3);
+}
+
+#[test]
+fn test_last_module_cannot_be_removed() {
+    let mut robot = miner_at((0, 0));
+    assert_eq!(robot.remove_module(Module::Mining), Err(RefitError::Empty));
+    assert_eq!(robot.remove_module(Module::Imaging), Err(RefitError::NotInstalled(Module::Imaging)));
+    assert_eq!(robot.modules, vec![Module::Mining]);
+}
+
+#[test]
+fn test_refit_waits_for_dock_and_stock() {
+    let mut station = Station::new((5, 5));
+    let mut robot = miner_at((2, 2));
+    station.order_refit(RefitOrder { robot_id: 1, install: vec![Module::Battery], remove: vec![] });
+
+    assert_eq!(station.refit(&mut robot), Err(RefitError::NotDocked));
+    robot.position = station.position;
+    assert!(matches!(station.refit(&mut robot), Err(RefitError::CannotAfford(_))));
+    assert_eq!(station.refits.len(), 1);
+
+    let cost = Module::Battery.build_cost();
+    station.energy = cost.energy;
+    station.ore = cost.ore;
+    assert_eq!(station.refit(&mut robot), Ok(true));
+    assert_eq!(robot.modules, vec![Module::Mining, Module::Battery]);
+    assert_eq!((station.energy, station.ore), (0, 0));
+    assert!(station.refits.is_empty());
+}
+
+#[test]
+fn test_removed_modules_are_reused_for_free() {
+    let mut station = Station::new((5, 5));
+    let mut robot = miner_at((5, 5));
+    robot.install_module(Module::CargoBay).unwrap();
+    let mut other = Robot::new(2, (5, 5), 50, Module::Imaging, Behavior::Exploration);
+
+    station.order_refit(RefitOrder { robot_id: 1, install: vec![], remove: vec![Module::CargoBay] });
+    station.order_refit(RefitOrder { robot_id: 2, install: vec![Module::CargoBay], remove: vec![] });
+    assert_eq!(station.refit(&mut robot), Ok(true));
+    assert_eq!(robot.cargo_capacity, DEFAULT_CARGO_CAPACITY);
+    assert_eq!(station.spare_modules, vec![Module::CargoBay]);
+
+    assert_eq!(station.refit(&mut other), Ok(true));
+    assert!(other.has_module(Module::CargoBay));
+    assert!(station.spare_modules.is_empty());
+}
+
+#[test]
+fn test_simulation_refits_docked_robots() {
+    let mut simulation = Simulation::from_map(Map::new(10, 10, 1), (5, 5), 3);
+    simulation.station.energy = 1000;
+    simulation.station.ore = 100;
+    // batterie vide : le robot reste à quai pendant le tick
+    simulation.station.robots[1].energy = 0;
+    simulation.station.order_refit(RefitOrder { robot_id: 2, install: vec![Module::Battery], remove: vec![] });
+    simulation.step();
+
+    let robot = simulation.station.robots.iter().find(|robot| robot.id == 2).unwrap();
+    assert_eq!(robot.modules, vec![Module::Mining, Module::Battery]);
+    assert_eq!(robot.energy, robot.battery_capacity());
+    assert_eq!(simulation.station.refit_stats.refitted, 1);
+
+    // trop cher : la commande attend et l'échec est compté
+    simulation.station.energy = 0;
+    simulation.station.robots[1].energy = 0;
+    simulation.station.order_refit(RefitOrder { robot_id: 2, install: vec![Module::Imaging], remove: vec![] });
+    simulation.step();
+    assert_eq!(simulation.station.refit_stats.failed, 1);
+    assert_eq!(simulation.station.refits.len(), 1);
+}
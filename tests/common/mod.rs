@@ -0,0 +1,8 @@
+use eerea::map::Map;
+
+// une carte sans aucun obstacle, construite comme les fixtures ASCII
+pub fn empty_map(width: usize, height: usize) -> Map {
+    let text = vec![".".repeat(width); height].join("\n");
+    let (map, _) = Map::from_ascii(&text).expect("valid ascii map");
+    map
+}
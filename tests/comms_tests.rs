@@ -0,0 +1,167 @@
+use eerea::comms::{self, CommsConfig, Network, Payload, ResourceClaim};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::empty_map;
+
+fn enabled(radius: usize) -> CommsConfig {
+    CommsConfig { enabled: true, radius, ..CommsConfig::default() }
+}
+
+#[test]
+fn test_map_fragment_reaches_robots_in_range_only() {
+    let mut map = empty_map(20, 20);
+    map.throw_resource_at(3, 3, Resource::Ore);
+    let mut robots = vec![
+        Robot::new(1, (3, 3), 100, Module::Imaging, Behavior::Exploration),
+        Robot::new(2, (6, 3), 100, Module::Mining, Behavior::ResourceCollection),
+        Robot::new(3, (15, 15), 100, Module::Mining, Behavior::ResourceCollection),
+    ];
+    robots[0].sense(&mut map, 4);
+    robots[1].sense(&mut map, 4);
+    robots[0].compose_messages(4);
+    let mut network = Network::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    comms::exchange(&mut robots, &mut network, &enabled(5), &mut rng, 4);
+    assert_eq!(robots[1].comms.inbox.len(), 1);
+    assert!(robots[2].comms.inbox.is_empty());
+
+    robots[1].read_inbox(5);
+    assert_eq!(robots[1].belief.content_at(3, 3), Some(TileContent::Resource(Resource::Ore)));
+}
+
+#[test]
+fn test_latency_delays_delivery() {
+    let mut robots = vec![
+        Robot::new(1, (3, 3), 100, Module::Imaging, Behavior::Exploration),
+        Robot::new(2, (4, 3), 100, Module::Imaging, Behavior::Exploration),
+    ];
+    let config = CommsConfig { latency: 2, ..enabled(5) };
+    let mut network = Network::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    let claim = ResourceClaim { robot_id: 1, position: (8, 8), tick: 0 };
+    robots[0].send(Some(2), Payload::ResourceClaim(claim), 0);
+    comms::exchange(&mut robots, &mut network, &config, &mut rng, 0);
+    comms::exchange(&mut robots, &mut network, &config, &mut rng, 1);
+    assert!(robots[1].comms.inbox.is_empty());
+    comms::exchange(&mut robots, &mut network, &config, &mut rng, 2);
+    assert_eq!(robots[1].comms.inbox.len(), 1);
+    assert_eq!(network.stats.delivered, 1);
+}
+
+#[test]
+fn test_total_packet_loss_drops_everything() {
+    let mut robots = vec![
+        Robot::new(1, (3, 3), 100, Module::Imaging, Behavior::Exploration),
+        Robot::new(2, (4, 3), 100, Module::Imaging, Behavior::Exploration),
+    ];
+    let config = CommsConfig { packet_loss: 1.0, ..enabled(5) };
+    let mut network = Network::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let claim = ResourceClaim { robot_id: 1, position: (8, 8), tick: 0 };
+    robots[0].send(None, Payload::ResourceClaim(claim), 0);
+    comms::exchange(&mut robots, &mut network, &config, &mut rng, 0);
+    assert!(robots[1].comms.inbox.is_empty());
+    assert_eq!(network.stats.lost, 1);
+}
+
+#[test]
+fn test_claimed_resource_is_left_to_the_claimer() {
+    let mut map = empty_map(20, 20);
+    map.throw_resource_at(5, 5, Resource::Ore);
+    let mut miner = Robot::new(2, (5, 4), 100, Module::Mining, Behavior::ResourceCollection);
+    miner.sense(&mut map, 1);
+    assert_eq!(miner.find_resource(), Some((5, 5)));
+
+    let claim = ResourceClaim { robot_id: 1, position: (5, 5), tick: 1 };
+    miner.comms.inbox.push(eerea::comms::Message { from: 1, to: None, sent: 1, payload: Payload::ResourceClaim(claim) });
+    miner.read_inbox(2);
+    assert_eq!(miner.find_resource(), None);
+    // la réservation expire si elle n'est pas renouvelée
+    miner.read_inbox(2 + eerea::comms::CLAIM_DURATION);
+    assert_eq!(miner.find_resource(), Some((5, 5)));
+}
+
+#[test]
+fn test_simulation_with_comms_is_deterministic() {
+    let mut first = Simulation::generate(20, 20, 14).expect("no free tile");
+    first.comms_config = CommsConfig { packet_loss: 0.2, latency: 1, ..enabled(6) };
+    let mut second = first.clone();
+    first.run(200);
+    second.run(200);
+    assert_eq!(first, second);
+    assert!(first.network.stats.delivered > 0);
+    assert!(first.network.stats.lost > 0);
+}
+
+#[test]
+fn test_distress_call_is_repeated_then_expires() {
+    let mut lost = Robot::new(1, (3, 3), 0, Module::Mining, Behavior::ResourceCollection);
+    lost.stranded = true;
+    lost.compose_messages(0);
+    lost.compose_messages(10);
+    assert_eq!(lost.comms.outbox.len(), 1);
+    lost.compose_messages(comms::DISTRESS_DURATION / 2);
+    assert_eq!(lost.comms.outbox.len(), 2);
+    // un tick plus ancien que le dernier appel ne le renouvelle pas
+    lost.compose_messages(5);
+    assert_eq!(lost.comms.outbox.len(), 2);
+
+    let mut listener = Robot::new(2, (4, 3), 100, Module::Imaging, Behavior::Exploration);
+    listener.comms.inbox = std::mem::take(&mut lost.comms.outbox);
+    listener.comms.inbox.truncate(1);
+    listener.read_inbox(1);
+    assert_eq!(listener.comms.distress_calls.len(), 1);
+    listener.read_inbox(1 + comms::DISTRESS_DURATION);
+    assert!(listener.comms.distress_calls.is_empty());
+}
+
+#[test]
+fn test_station_sends_help_to_stranded_robot() {
+    let mut simulation = Simulation::from_map(empty_map(12, 12), (2, 2), 3);
+    simulation.comms_config = enabled(4);
+    simulation.station.robots[0].position = (8, 8);
+    simulation.station.robots[0].energy = 0;
+
+    simulation.run(2);
+    assert_eq!(simulation.station.distress_calls().len(), 1);
+    let mut rescued_at = None;
+    for _ in 0..40 {
+        simulation.step();
+        if simulation.network.stats.rescues > 0 {
+            rescued_at = Some(simulation.tick);
+            break;
+        }
+    }
+    assert!(rescued_at.is_some());
+    assert!(simulation.station.robots[0].energy > 0);
+    assert!(simulation.station.distress_calls().is_empty());
+    assert!(simulation.station.robots.iter().all(|robot| robot.comms.distress_calls.is_empty()));
+}
+
+#[test]
+fn test_stranded_robot_is_rescued_without_radio() {
+    let mut simulation = Simulation::from_map(empty_map(12, 12), (2, 2), 3);
+    assert!(!simulation.comms_config.enabled);
+    simulation.station.robots[0].position = (8, 8);
+    simulation.station.robots[0].energy = 0;
+
+    simulation.run(2);
+    assert_eq!(simulation.station.distress_calls().len(), 1);
+    for _ in 0..40 {
+        simulation.step();
+        if simulation.network.stats.rescues > 0 {
+            break;
+        }
+    }
+    assert_eq!(simulation.network.stats.rescues, 1);
+    assert!(simulation.station.robots[0].energy > 0);
+    assert!(!simulation.station.robots[0].stranded);
+    assert!(simulation.station.distress_calls().is_empty());
+}
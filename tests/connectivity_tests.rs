@@ -0,0 +1,57 @@
+use eerea::connectivity::{self, Connectivity, StationPlacement};
+use eerea::map::{Map, MapGenConfig};
+use eerea::tile::Resource;
+
+// deux salles séparées par un mur, de l'énergie dans celle de droite
+const SPLIT_MAP: &str = "\
+#########
+#...#..E#
+#...#...#
+#...#...#
+#########
+";
+
+#[test]
+fn test_regions() {
+    let (map, _) = Map::from_ascii(SPLIT_MAP).unwrap();
+    let regions = connectivity::regions(&map);
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].len(), 9);
+}
+
+#[test]
+fn test_analyze_reports_unreachable_resources() {
+    let (map, _) = Map::from_ascii(SPLIT_MAP).unwrap();
+    let report = connectivity::analyze(&map, (1, 1));
+    assert_eq!(report.reachable_tiles, 9);
+    assert_eq!(report.unreachable_resources, vec![((7, 1), Resource::Energy)]);
+}
+
+#[test]
+fn test_carve_corridors_connects_everything() {
+    let (mut map, _) = Map::from_ascii(SPLIT_MAP).unwrap();
+    let carved = connectivity::carve_corridors(&mut map);
+    assert_eq!(carved, 1);
+    assert_eq!(connectivity::regions(&map).len(), 1);
+    assert!(connectivity::analyze(&map, (1, 1)).unreachable_resources.is_empty());
+}
+
+#[test]
+fn test_station_placement() {
+    let (map, _) = Map::from_ascii(SPLIT_MAP).unwrap();
+    assert_eq!(connectivity::place_station(&map, StationPlacement::FirstFree), Some((1, 1)));
+    assert_eq!(connectivity::place_station(&map, StationPlacement::CentreMost), Some((3, 2)));
+    assert_eq!(connectivity::place_station(&map, StationPlacement::MaxReachableArea), Some((2, 2)));
+    assert_eq!(connectivity::place_station(&map, StationPlacement::Fixed((7, 1))), None);
+}
+
+#[test]
+fn test_generate_world_is_connected() {
+    for connectivity in [Connectivity::CarveCorridors, Connectivity::Reseed { max_attempts: 5 }] {
+        let config = MapGenConfig { connectivity, obstacle_threshold: 0.2, ..MapGenConfig::default() };
+        let (map, report) = connectivity::generate_world(40, 40, 14, &config, StationPlacement::CentreMost).unwrap();
+        assert_eq!(report.regions, 1);
+        assert!(report.unreachable_resources.is_empty());
+        assert!(map.is_empty(report.station.0, report.station.1));
+    }
+}
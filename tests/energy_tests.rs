@@ -0,0 +1,152 @@
+use eerea::behavior::{Action, BehaviorRegistry};
+use eerea::energy::{EnergyModel, ReturnPlanner};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::empty_map;
+
+#[test]
+fn test_carrying_a_load_makes_moves_dearer() {
+    let model = EnergyModel::default();
+    assert_eq!(model.action_cost(&Action::MoveRandomly, 0), model.move_cost);
+    assert_eq!(model.action_cost(&Action::MoveRandomly, model.carry_step), model.move_cost + model.carry_cost);
+    assert_eq!(model.action_cost(&Action::Mine, 5), model.mine_cost);
+}
+
+#[test]
+fn test_mining_is_charged_with_mine_cost() {
+    let mut map = empty_map(10, 10);
+    map.throw_resource_at(2, 2, Resource::Ore);
+    let model = EnergyModel { mine_cost: 7, ..EnergyModel::default() };
+    let mut robot = Robot::new(1, (2, 2), 50, Module::Mining, Behavior::ResourceCollection);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action_with(&BehaviorRegistry::default(), &model, &mut map, (5, 5), &mut rng, 0);
+    assert_eq!(robot.cargo, vec![Resource::Ore]);
+    assert_eq!(robot.energy, 43);
+}
+
+#[test]
+fn test_robot_without_energy_is_stranded() {
+    let mut map = empty_map(10, 10);
+    let mut robot = Robot::new(1, (2, 2), 0, Module::Imaging, Behavior::Exploration);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for tick in 0..10 {
+        robot.perform_action(&mut map, (5, 5), &mut rng, tick);
+    }
+    assert_eq!(robot.position, (2, 2));
+    assert_eq!(robot.energy, 0);
+}
+
+#[test]
+fn test_unaffordable_action_is_skipped() {
+    let mut map = empty_map(10, 10);
+    map.throw_resource_at(2, 2, Resource::Energy);
+    let mut robot = Robot::new(1, (2, 2), 1, Module::Mining, Behavior::ResourceCollection);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action(&mut map, (5, 5), &mut rng, 0);
+    assert!(robot.cargo.is_empty());
+    assert_eq!(map.tiles[2][2].content, TileContent::Resource(Resource::Energy));
+}
+
+#[test]
+fn test_refill_uses_battery_capacity() {
+    let mut robot = Robot::new(1, (0, 0), 3, Module::Mining, Behavior::ResourceCollection);
+    robot.refill_energy();
+    assert_eq!(robot.energy, Module::Mining.battery_capacity());
+    assert_ne!(Module::Mining.battery_capacity(), Module::Imaging.battery_capacity());
+}
+
+#[test]
+fn test_return_reserve_follows_the_known_path() {
+    let mut map = empty_map(10, 10);
+    // un mur entre le robot et la station, le retour fait le tour
+    for y in 0..8 {
+        map.tiles[y][5].content = TileContent::Obstacle;
+    }
+    let mut robot = Robot::new(1, (3, 1), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 0);
+    let model = EnergyModel { return_planner: ReturnPlanner { margin_percent: 0, margin: 0, ..ReturnPlanner::default() }, ..EnergyModel::default() };
+    let straight = 4;
+    assert!(model.return_planner.energy_to_reach(&robot, (7, 1), &model) > straight);
+}
+
+#[test]
+fn test_low_energy_robot_heads_home_before_running_dry() {
+    let mut map = empty_map(10, 10);
+    let mut robot = Robot::new(1, (8, 8), 100, Module::Imaging, Behavior::Exploration);
+    let model = EnergyModel::default();
+    robot.energy = model.return_planner.reserve(&robot, (1, 1), &model);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for tick in 0..30 {
+        robot.perform_action_with(&BehaviorRegistry::default(), &model, &mut map, (1, 1), &mut rng, tick);
+    }
+    assert_eq!(robot.position, (1, 1));
+    assert!(robot.energy > 0);
+}
+
+#[test]
+fn test_simulation_counts_returns_and_dry_robots() {
+    let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    simulation.run(400);
+    assert!(simulation.energy_stats.returns > 0);
+    assert_eq!(simulation.energy_stats.ran_dry, 0);
+
+    // sans planificateur, les robots tombent en panne dehors
+    let mut reckless = Simulation::generate(20, 20, 14).expect("no free tile");
+    reckless.energy_model.return_planner.enabled = false;
+    reckless.run(400);
+    assert!(reckless.energy_stats.ran_dry > 0);
+    assert!(reckless.stranded_robots() > 0);
+}
+
+#[test]
+fn test_robot_that_cannot_afford_a_step_is_stranded() {
+    let mut simulation = Simulation::from_map(empty_map(10, 10), (1, 1), 4);
+    simulation.energy_model = EnergyModel { move_cost: 3, ..EnergyModel::default() };
+    simulation.comms_config.enabled = true;
+    let robot = &mut simulation.station.robots[0];
+    robot.position = (7, 7);
+    robot.energy = 2;
+
+    simulation.step();
+    let robot = &simulation.station.robots[0];
+    assert_eq!(robot.energy, 2);
+    assert!(robot.stranded);
+    assert!(robot.comms.distress_sent.is_some());
+    assert_eq!(simulation.stranded_robots(), 1);
+    assert_eq!(simulation.energy_stats.ran_dry, 1);
+}
+
+#[test]
+fn test_return_estimate_is_an_upper_bound_refreshed_near_the_threshold() {
+    let mut map = empty_map(20, 20);
+    // le mur oblige à un long détour pour rentrer en (2, 10)
+    for y in 0..19 {
+        map.tiles[y][5].content = TileContent::Obstacle;
+    }
+    let mut robot = Robot::new(1, (8, 10), 100, Module::Imaging, Behavior::Exploration);
+    robot.belief.ensure_size(20, 20);
+    for y in 0..20 {
+        for x in 0..20 {
+            robot.belief.observe(x, y, map.tiles[y][x].content, 0);
+        }
+    }
+    let model = EnergyModel::default();
+    let planner = model.return_planner;
+    let reserve = planner.reserve(&robot, (2, 10), &model);
+
+    // la ligne droite ne suffit pas à dire qu'on peut continuer : il faut le vrai chemin
+    robot.energy = reserve;
+    assert!(planner.should_return(&mut robot, (2, 10), &model));
+    assert_eq!(robot.home.steps, Some(24));
+
+    // avec de la marge, la borne haute (chemin connu + pas faits depuis) suffit
+    robot.energy = reserve + 50;
+    robot.home.walked = 3;
+    assert!(!planner.should_return(&mut robot, (2, 10), &model));
+    assert_eq!(robot.home.steps, Some(24));
+}
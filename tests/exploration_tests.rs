@@ -0,0 +1,113 @@
+use eerea::behavior::{Action, ExplorationBehavior, PatrolBehavior, RobotBehavior, WorldView};
+use eerea::belief::{BeliefMap, STALE_AFTER};
+use eerea::knowledge::{KnowledgeBase, KnownTile};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::station::Station;
+use eerea::tasks::{TaskKind, MAX_PATROL_TASKS};
+use eerea::tile::TileContent;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn known_belief(width: usize, height: usize, tick: u64) -> BeliefMap {
+    let mut belief = BeliefMap::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            belief.observe(x, y, TileContent::Empty, tick);
+        }
+    }
+    belief
+}
+
+#[test]
+fn test_nearest_frontier_follows_known_paths() {
+    let mut belief = BeliefMap::new(10, 10);
+    for y in 0..10 {
+        for x in 0..5 {
+            belief.observe(x, y, TileContent::Empty, 0);
+        }
+    }
+    // un mur en x = 2, avec un passage tout en haut
+    for y in 1..10 {
+        belief.observe(2, y, TileContent::Obstacle, 0);
+    }
+    assert!(belief.is_frontier(4, 5));
+    assert!(!belief.is_frontier(3, 5));
+    assert!(!belief.is_frontier(2, 5));
+    // (4, 5) est plus près à vol d'oiseau mais il faut passer par le haut
+    assert_eq!(belief.nearest_frontier((0, 5)), Some((4, 0)));
+    assert_eq!(known_belief(5, 5, 0).nearest_frontier((0, 0)), None);
+}
+
+#[test]
+fn test_stalest_tile_prefers_oldest_then_nearest() {
+    let mut belief = known_belief(10, 10, 50);
+    belief.observe(9, 9, TileContent::Empty, 10);
+    belief.observe(1, 0, TileContent::Empty, 10);
+    belief.observe(0, 0, TileContent::Empty, 0);
+    belief.observe(5, 5, TileContent::Obstacle, 0);
+    // on ne se propose jamais sa propre tuile ni un obstacle
+    assert_eq!(belief.stalest_tile((0, 0)), Some((1, 0)));
+    assert_eq!(belief.stalest_tile((9, 8)), Some((0, 0)));
+}
+
+#[test]
+fn test_explorer_heads_for_the_frontier_then_patrols() {
+    let mut robot = Robot::new(1, (1, 2), 100, Module::Imaging, Behavior::Exploration);
+    robot.belief = BeliefMap::new(10, 10);
+    for y in 0..5 {
+        for x in 0..5 {
+            robot.belief.observe(x, y, TileContent::Empty, 0);
+        }
+    }
+    let world = WorldView { station_position: (0, 0), tick: 0 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    assert_eq!(ExplorationBehavior.decide(&robot, &world, &mut rng), Action::ExploreTowards((1, 4)));
+
+    robot.belief = known_belief(10, 10, 20);
+    robot.belief.observe(7, 8, TileContent::Empty, 3);
+    assert_eq!(ExplorationBehavior.decide(&robot, &world, &mut rng), Action::ExploreTowards((7, 8)));
+}
+
+#[test]
+fn test_patrol_behavior_revisits_oldest_tile() {
+    let mut robot = Robot::new(1, (5, 5), 100, Module::Mining, Behavior::Patrol);
+    robot.belief = known_belief(10, 10, 100);
+    robot.belief.observe(0, 9, TileContent::Empty, 40);
+    let world = WorldView { station_position: (0, 0), tick: 100 };
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    assert_eq!(PatrolBehavior.decide(&robot, &world, &mut rng), Action::ExploreTowards((0, 9)));
+}
+
+#[test]
+fn test_station_posts_patrols_for_stale_regions() {
+    let mut station = Station::new((5, 5));
+    station.known_tiles = KnowledgeBase::new(10, 10);
+    for y in 0..10 {
+        for x in 0..10 {
+            station.known_tiles.merge(x, y, KnownTile { content: TileContent::Empty, observer: 9, tick: 0 });
+        }
+    }
+    let mut robot = Robot::new(1, (5, 5), 100, Module::Imaging, Behavior::Exploration);
+    robot.belief = BeliefMap::new(10, 10);
+    station.robots = vec![robot];
+
+    // rien n'est encore périmé
+    station.plan_tasks(STALE_AFTER);
+    assert!(station.task_board.tasks.is_empty());
+
+    let tick = STALE_AFTER + 1;
+    station.plan_tasks(tick);
+    let patrols = station.task_board.tasks.iter().filter(|task| matches!(task.kind, TaskKind::Patrol(_))).count();
+    assert_eq!(patrols, MAX_PATROL_TASKS);
+    let task = station.robots[0].task.expect("patrol assigned");
+    let TaskKind::Patrol(target) = task.kind else {
+        panic!("expected a patrol, got {:?}", task.kind);
+    };
+
+    // le robot revoit la tuile puis la synchronise avec la station
+    station.robots[0].belief.observe(target.0, target.1, TileContent::Empty, tick + 1);
+    station.known_tiles.merge(target.0, target.1, KnownTile { content: TileContent::Empty, observer: 1, tick: tick + 1 });
+    station.plan_tasks(tick + 1);
+    assert_eq!(station.task_board.completed, 1);
+    assert!(!station.task_board.contains(&TaskKind::Patrol(target)));
+}
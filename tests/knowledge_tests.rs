@@ -0,0 +1,68 @@
+use eerea::knowledge::{KnowledgeBase, KnownTile, MergeOutcome};
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::station::Station;
+use eerea::tile::{Resource, TileContent};
+
+fn seen(content: TileContent, observer: usize, tick: u64) -> KnownTile {
+    KnownTile { content, observer, tick }
+}
+
+#[test]
+fn test_newer_observation_wins() {
+    let mut knowledge = KnowledgeBase::new(5, 5);
+    let ore = TileContent::Resource(Resource::Ore);
+    assert_eq!(knowledge.merge(2, 2, seen(ore, 1, 10)), MergeOutcome::Added);
+    assert_eq!(knowledge.merge(2, 2, seen(TileContent::Empty, 2, 20)), MergeOutcome::Replaced);
+    // un robot qui rentre tard avec une vieille observation ne fait pas réapparaître le minerai
+    assert_eq!(knowledge.merge(2, 2, seen(ore, 3, 15)), MergeOutcome::Rejected);
+    assert_eq!(knowledge.get(2, 2), Some(&seen(TileContent::Empty, 2, 20)));
+}
+
+#[test]
+fn test_same_tick_conflict_prefers_depleted_tile() {
+    let mut knowledge = KnowledgeBase::new(5, 5);
+    let energy = TileContent::Resource(Resource::Energy);
+    knowledge.merge(1, 1, seen(TileContent::Empty, 1, 7));
+    assert_eq!(knowledge.merge(1, 1, seen(energy, 2, 7)), MergeOutcome::Rejected);
+
+    knowledge.merge(3, 3, seen(energy, 2, 7));
+    assert_eq!(knowledge.merge(3, 3, seen(TileContent::Empty, 1, 7)), MergeOutcome::Replaced);
+}
+
+#[test]
+fn test_confirmation_refreshes_tick() {
+    let mut knowledge = KnowledgeBase::new(5, 5);
+    knowledge.merge(0, 0, seen(TileContent::Obstacle, 1, 4));
+    assert_eq!(knowledge.merge(0, 0, seen(TileContent::Obstacle, 2, 9)), MergeOutcome::Confirmed);
+    assert_eq!(knowledge.get(0, 0).unwrap().tick, 9);
+    assert_eq!(knowledge.merge(9, 9, seen(TileContent::Obstacle, 2, 9)), MergeOutcome::Rejected);
+    assert_eq!(knowledge.len(), 1);
+}
+
+#[test]
+fn test_station_keeps_mined_tile_from_later_robot() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(2, 2, Resource::Ore);
+    let mut scout = Robot::new(1, (2, 2), 100, Module::Imaging, Behavior::Exploration);
+    scout.sense(&mut map, 1);
+    let mut miner = Robot::new(2, (2, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    miner.sense(&mut map, 2);
+    map.retrieve_resource_at(2, 2);
+    miner.sense(&mut map, 3);
+
+    let mut station = Station::new((5, 5));
+    station.collect_data(&miner);
+    station.collect_data(&scout);
+    assert_eq!(station.known_tiles.get(2, 2), Some(&seen(TileContent::Empty, 2, 3)));
+}
+
+#[test]
+fn test_knowledge_base_survives_json() {
+    let mut knowledge = KnowledgeBase::new(4, 3);
+    knowledge.merge(3, 2, seen(TileContent::Resource(Resource::PlaceOfInterest), 1, 5));
+    let json = serde_json::to_string(&knowledge).unwrap();
+    let loaded: KnowledgeBase = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, knowledge);
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), vec![((3, 2), &seen(TileContent::Resource(Resource::PlaceOfInterest), 1, 5))]);
+}
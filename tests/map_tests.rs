@@ -1,4 +1,4 @@
-use eerea::map::Map;
+use eerea::map::{Map, MapError, MapGenConfig, ResourceWeights};
 use eerea::tile::{TileContent, Resource};
 
 #[test]
@@ -8,6 +8,15 @@ fn test_generate_tiles() {
     assert_eq!(map.height, 10);
 }
 
+// les graines qui ne diffèrent que par leurs 32 bits de poids fort ne donnent pas le même relief
+#[test]
+fn test_high_seed_bits_change_terrain() {
+    let obstacles = |seed: u64| -> Vec<bool> {
+        Map::generate_tiles(30, 30, seed).iter().flatten().map(|tile| tile.content == TileContent::Obstacle).collect()
+    };
+    assert_ne!(obstacles(5), obstacles(5 + (1 << 32)));
+}
+
 #[test]
 fn test_check_bounds() {
     let map = Map::new(10, 10, 1);
@@ -35,12 +44,13 @@ fn test_throw_resource_at() {
 
 #[test]
 fn test_tile_at() {
-    let map = Map::new(10, 10, 1);
-    if let Some(tile) = map.tile_at(2, 2) {
-        assert_eq!(tile.content, TileContent::Empty); 
-    } else {
-        panic!("Tile not found");
-    }
+    let mut map = Map::new(10, 10, 1);
+    map.tiles[2][3].content = TileContent::Resource(Resource::Ore);
+    map.tiles[3][2].content = TileContent::Obstacle;
+    // tiles est rangé ligne par ligne : tile_at(x, y) lit tiles[y][x]
+    assert_eq!(map.tile_at(3, 2).map(|tile| tile.content), Some(TileContent::Resource(Resource::Ore)));
+    assert_eq!(map.tile_at(2, 3).map(|tile| tile.content), Some(TileContent::Obstacle));
+    assert!(map.tile_at(10, 10).is_none());
 }
 
 #[test]
@@ -67,4 +77,110 @@ fn test_retrieve_resource_at() {
     } else {
         panic!("Tile not found");
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_deposit_depletes_one_unit_at_a_time() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_deposit_at(1, 1, Resource::Ore, 3);
+    assert_eq!(map.retrieve_resource_at(1, 1), Some(Resource::Ore));
+    assert_eq!(map.deposit_at(1, 1), Some((Resource::Ore, 2)));
+    map.retrieve_resource_at(1, 1);
+    map.retrieve_resource_at(1, 1);
+    assert_eq!(map.deposit_at(1, 1), None);
+    assert_eq!(map.tile_at(1, 1).unwrap().content, TileContent::Empty);
+    assert_eq!(map.retrieve_resource_at(1, 1), None);
+}
+
+#[test]
+fn test_generated_deposits_stay_within_bounds() {
+    let config = MapGenConfig { min_deposit: 2, max_deposit: 6, resource_probability: 1.0, ..MapGenConfig::default() };
+    let map = Map::with_config(20, 20, 3, &config);
+    let plain = Map::with_config(20, 20, 3, &MapGenConfig { resource_probability: 1.0, ..MapGenConfig::default() });
+    for (row, plain_row) in map.tiles.iter().zip(&plain.tiles) {
+        for (tile, plain_tile) in row.iter().zip(plain_row) {
+            // les quantités ne changent pas la disposition
+            assert_eq!(tile.content, plain_tile.content);
+            if matches!(tile.content, TileContent::Resource(Resource::Energy | Resource::Ore)) {
+                assert!((2..=6).contains(&tile.quantity));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_ascii() {
+    let (map, station) = Map::from_ascii(include_str!("fixtures/corridor.txt")).unwrap();
+    assert_eq!(map.width, 10);
+    assert_eq!(map.height, 7);
+    assert_eq!(station, Some((1, 1)));
+    assert!(map.is_empty(1, 1));
+    assert_eq!(map.tile_at(8, 1).unwrap().content, TileContent::Resource(Resource::Energy));
+    assert_eq!(map.tile_at(3, 3).unwrap().content, TileContent::Resource(Resource::Ore));
+    assert_eq!(map.tile_at(8, 3).unwrap().content, TileContent::Resource(Resource::PlaceOfInterest));
+}
+
+#[test]
+fn test_ascii_roundtrip() {
+    let text = include_str!("fixtures/corridor.txt");
+    let (map, station) = Map::from_ascii(text).unwrap();
+    assert_eq!(map.to_ascii(station), text);
+
+    let generated = Map::new(15, 12, 3);
+    let (parsed, _) = Map::from_ascii(&generated.to_ascii(None)).unwrap();
+    assert_eq!(parsed.to_ascii(None), generated.to_ascii(None));
+}
+
+#[test]
+fn test_from_ascii_errors() {
+    assert!(matches!(Map::from_ascii(""), Err(MapError::Empty)));
+    assert!(matches!(Map::from_ascii("###\n##\n"), Err(MapError::RaggedRow { y: 1, .. })));
+    assert!(matches!(Map::from_ascii("#x#\n"), Err(MapError::UnknownChar { ch: 'x', x: 1, y: 0 })));
+    assert!(matches!(Map::from_ascii("S.S\n"), Err(MapError::MultipleStations)));
+}
+
+#[test]
+fn test_json_roundtrip() {
+    let map = Map::new(12, 8, 4);
+    let restored = Map::from_json(&map.to_json().unwrap()).unwrap();
+    assert_eq!(restored, map);
+}
+
+#[test]
+fn test_default_config_matches_map_new() {
+    let config = MapGenConfig::default();
+    assert_eq!(Map::with_config(30, 20, 6, &config), Map::new(30, 20, 6));
+}
+
+#[test]
+fn test_config_border_and_weights() {
+    let config = MapGenConfig {
+        obstacle_threshold: 2.0,
+        resource_probability: 1.0,
+        resource_weights: ResourceWeights { energy: 0, ore: 1, place_of_interest: 0 },
+        border_thickness: 3,
+        ..MapGenConfig::default()
+    };
+    let map = Map::with_config(12, 12, 2, &config);
+    for y in 0..12 {
+        for x in 0..12 {
+            let border = x < 3 || y < 3 || x >= 9 || y >= 9;
+            let expected = if border { TileContent::Obstacle } else { TileContent::Resource(Resource::Ore) };
+            assert_eq!(map.tiles[y][x].content, expected);
+        }
+    }
+}
+
+#[test]
+fn test_config_from_toml_and_json() {
+    let config = MapGenConfig::from_toml("octaves = 3\nobstacle_threshold = 0.2\n[resource_weights]\nore = 5\n").unwrap();
+    assert_eq!(config.octaves, 3);
+    assert_eq!(config.obstacle_threshold, 0.2);
+    assert_eq!(config.resource_weights.ore, 5);
+    assert_eq!(config.resource_weights.energy, 1);
+    assert_eq!(config.noise_scale, 5.0);
+
+    let config = MapGenConfig::from_json(r#"{"border_thickness": 2}"#).unwrap();
+    assert_eq!(config.border_thickness, 2);
+    assert_eq!(config.resource_probability, 0.3);
+}
@@ -0,0 +1,97 @@
+use eerea::map::Map;
+use eerea::pathfinding::{find_path, path_cost};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// une carte vide entourée de murs
+fn open_map(width: usize, height: usize) -> Map {
+    let mut map = Map::new(width, height, 1);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            map.tiles[y][x].content = TileContent::Empty;
+        }
+    }
+    map
+}
+
+#[test]
+fn test_find_path_shortest() {
+    let map = open_map(10, 10);
+    let route = find_path(&map, (1, 1), (4, 3)).unwrap();
+    assert_eq!(route.steps.len(), 5);
+    assert_eq!(route.steps.last(), Some(&(4, 3)));
+    assert_eq!(route.remaining_cost(), 5);
+}
+
+#[test]
+fn test_find_path_avoids_costly_terrain() {
+    let mut map = open_map(10, 10);
+    // un mur avec un passage de minerai au milieu, et un détour vide juste au-dessus
+    for y in 2..9 {
+        map.tiles[y][4].content = TileContent::Obstacle;
+    }
+    map.throw_resource_at(4, 5, Resource::Ore);
+    assert_eq!(path_cost(&map, (2, 5), (6, 5)), Some(5));
+    map.throw_resource_at(4, 1, Resource::Ore);
+    map.tiles[5][4].content = TileContent::Obstacle;
+    assert_eq!(path_cost(&map, (2, 5), (6, 5)), Some(13));
+}
+
+#[test]
+fn test_find_path_unreachable() {
+    let mut map = open_map(10, 10);
+    for y in 0..10 {
+        map.tiles[y][5].content = TileContent::Obstacle;
+    }
+    assert!(find_path(&map, (1, 1), (8, 8)).is_none());
+}
+
+#[test]
+fn test_robot_reuses_cached_route() {
+    let map = open_map(200, 200);
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Imaging, Behavior::Exploration);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.move_towards_goal(&map, None, (150, 150), &mut rng);
+    let planned = robot.route.clone().unwrap();
+    robot.move_towards_goal(&map, None, (150, 150), &mut rng);
+    let route = robot.route.as_ref().unwrap();
+    assert_eq!(route.steps, planned.steps);
+    assert_eq!(route.next, 2);
+    assert_eq!(robot.position, planned.steps[1]);
+}
+
+#[test]
+fn test_robot_replans_when_map_changes_on_route() {
+    let mut map = open_map(20, 20);
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Imaging, Behavior::Exploration);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.move_towards_goal(&map, None, (10, 10), &mut rng);
+    let blocked = robot.route.as_ref().unwrap().steps[1];
+    map.tiles[blocked.1][blocked.0].content = TileContent::Obstacle;
+    // le robot doit voir l'obstacle pour changer d'avis
+    robot.sense(&mut map, 1);
+    robot.move_towards_goal(&map, None, (10, 10), &mut rng);
+    let route = robot.route.as_ref().unwrap();
+    assert!(!route.steps.contains(&blocked));
+    assert_eq!(route.next, 1);
+}
+
+#[test]
+fn test_robot_path_uses_belief_not_map() {
+    let mut map = open_map(10, 10);
+    for y in 0..8 {
+        map.tiles[y][5].content = TileContent::Obstacle;
+    }
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Imaging, Behavior::Exploration);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    // le mur n'a jamais été vu : le robot fonce tout droit dessus
+    robot.move_towards_goal(&map, None, (8, 1), &mut rng);
+    assert_eq!(robot.route.as_ref().unwrap().steps.len(), 7);
+    for tick in 0..40 {
+        robot.sense(&mut map, tick);
+        robot.move_towards_goal(&map, None, (8, 1), &mut rng);
+    }
+    assert_eq!(robot.position, (8, 1));
+}
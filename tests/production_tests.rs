@@ -0,0 +1,115 @@
+use eerea::production::{BuildOrder, BuildPolicy, FixedPolicy, SwarmNeedsPolicy, REPLAN_AFTER};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::station::Station;
+
+fn scout_order() -> BuildOrder {
+    BuildOrder { module: Module::Imaging, behavior: Behavior::Exploration }
+}
+
+#[test]
+fn test_order_waits_until_station_can_pay() {
+    let mut station = Station::new((5, 5));
+    let policy = FixedPolicy(scout_order());
+    assert_eq!(station.update_production(&policy), None);
+    assert_eq!(station.production.queue.len(), 1);
+    assert!(station.production.current.is_none());
+
+    let cost = Module::Imaging.build_cost();
+    station.energy = cost.energy + 10;
+    station.ore = cost.ore;
+    station.update_production(&policy);
+    assert!(station.production.current.is_some());
+    assert_eq!(station.energy, 10);
+    assert_eq!(station.ore, 0);
+}
+
+#[test]
+fn test_cheaper_order_goes_ahead_of_a_blocked_one() {
+    let mut station = Station::new((5, 5));
+    station.production.queue = vec![BuildOrder { module: Module::Analysis, behavior: Behavior::ScientificInterest }, scout_order()];
+    let cost = Module::Imaging.build_cost();
+    station.energy = cost.energy;
+    station.ore = cost.ore;
+    station.update_production(&FixedPolicy(scout_order()));
+    assert_eq!(station.production.current.as_ref().map(|production| production.order.module), Some(Module::Imaging));
+    assert_eq!(station.production.queue.len(), 1);
+}
+
+#[test]
+fn test_blocked_order_is_replanned_by_the_policy() {
+    let mut station = Station::new((5, 5));
+    station.production.queue = vec![BuildOrder { module: Module::Analysis, behavior: Behavior::ScientificInterest }];
+    let cost = Module::Imaging.build_cost();
+    station.energy = cost.energy;
+    station.ore = cost.ore;
+    let policy = FixedPolicy(scout_order());
+
+    for _ in 1..REPLAN_AFTER {
+        station.update_production(&policy);
+    }
+    assert_eq!(station.production.queue[0].module, Module::Analysis);
+    // la politique remplace la commande trop chère, lancée au tick suivant
+    station.update_production(&policy);
+    assert_eq!(station.production.queue, vec![scout_order()]);
+    station.update_production(&policy);
+    assert!(station.production.current.is_some());
+
+    // plus besoin de rien : la commande bloquée est abandonnée
+    let mut station = Station::new((5, 5));
+    station.production.queue = vec![scout_order()];
+    let full = SwarmNeedsPolicy { max_robots: 0, ..SwarmNeedsPolicy::default() };
+    for _ in 0..REPLAN_AFTER {
+        station.update_production(&full);
+    }
+    assert!(station.production.queue.is_empty());
+}
+
+#[test]
+fn test_robot_is_built_after_build_time_and_only_once() {
+    let mut station = Station::new((5, 5));
+    station.robots.push(Robot::new(1, (5, 5), 100, Module::Mining, Behavior::ResourceCollection));
+    let cost = Module::Imaging.build_cost();
+    station.energy = cost.energy;
+    station.ore = cost.ore;
+    let policy = FixedPolicy(scout_order());
+
+    let mut built = None;
+    let mut ticks = 0;
+    while built.is_none() {
+        built = station.update_production(&policy);
+        ticks += 1;
+    }
+    assert_eq!(ticks, cost.ticks);
+    assert_eq!(built, Some(2));
+    assert_eq!(station.robots.len(), 2);
+    assert_eq!(station.robots[1].modules, vec![Module::Imaging]);
+    assert_eq!(station.production.built, 1);
+}
+
+#[test]
+fn test_swarm_needs_policy_builds_missing_miners_first() {
+    let mut station = Station::new((5, 5));
+    station.robots.push(Robot::new(1, (5, 5), 100, Module::Mining, Behavior::ResourceCollection));
+    let policy = SwarmNeedsPolicy::default();
+    assert_eq!(policy.next_build(&station).map(|order| order.module), Some(Module::Mining));
+
+    station.robots.push(Robot::new(2, (5, 5), 100, Module::Mining, Behavior::ResourceCollection));
+    // rien n'est connu de la carte : il faut des éclaireurs
+    assert_eq!(policy.next_build(&station).map(|order| order.module), Some(Module::Imaging));
+
+    let full = SwarmNeedsPolicy { max_robots: 2, ..SwarmNeedsPolicy::default() };
+    assert_eq!(full.next_build(&station), None);
+}
+
+#[test]
+fn test_simulation_never_duplicates_robots() {
+    let mut simulation = Simulation::generate(30, 30, 14).expect("no free tile");
+    simulation.run(1000);
+    let mut ids: Vec<_> = simulation.station.robots.iter().map(|robot| robot.id).collect();
+    let count = ids.len();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), count);
+    assert_eq!(count as u64, 3 + simulation.station.production.built);
+}
@@ -0,0 +1,112 @@
+use eerea::behavior::BehaviorRegistry;
+use eerea::production::{BuildOrder, FixedPolicy, PolicyHandle};
+use eerea::replay::{Event, Player, Recorder, ReplayError, ReplayLog};
+use eerea::robot::{Behavior, Module};
+use eerea::simulation::Simulation;
+
+fn recorded_run(n_ticks: u64) -> (Simulation, ReplayLog) {
+    let mut simulation = Simulation::generate(20, 20, 9).expect("no free tile");
+    let mut recorder = Recorder::new(&simulation);
+    recorder.run(&mut simulation, n_ticks);
+    (simulation, recorder.into_log())
+}
+
+#[test]
+fn test_recorder_logs_moves() {
+    let (_, log) = recorded_run(20);
+    assert_eq!(log.ticks.len(), 20);
+    assert!(log.ticks.iter().flat_map(|t| &t.events).any(|e| matches!(e, Event::RobotMoved { .. })));
+}
+
+#[test]
+fn test_player_rebuilds_final_state() {
+    let (simulation, log) = recorded_run(250);
+    let mut player = Player::new(log);
+    player.seek(250).unwrap();
+    assert!(player.is_finished());
+    assert_eq!(player.simulation(), &simulation);
+}
+
+#[test]
+fn test_player_step_backward() {
+    let (_, log) = recorded_run(150);
+    let mut player = Player::new(log);
+    player.fast_forward(120).unwrap();
+    let at_120 = player.simulation().clone();
+    player.step_forward().unwrap();
+    player.step_backward().unwrap();
+    assert_eq!(player.tick(), 120);
+    assert_eq!(player.simulation(), &at_120);
+}
+
+#[test]
+fn test_player_detects_desync() {
+    let (_, mut log) = recorded_run(10);
+    log.ticks[3].events.clear();
+    log.ticks[3].events.push(Event::RobotRecharged { robot_id: 42, energy: 1 });
+    let mut player = Player::new(log);
+    assert!(matches!(player.seek(10), Err(ReplayError::Desync { tick: 3 })));
+}
+
+#[test]
+fn test_replay_log_json_roundtrip() {
+    let (_, log) = recorded_run(30);
+    let restored = ReplayLog::from_json(&log.to_json().unwrap()).unwrap();
+    assert_eq!(restored, log);
+    assert!(matches!(Player::new(restored).seek(31), Err(ReplayError::OutOfRange { .. })));
+}
+
+#[test]
+fn test_bounded_recorder_forgets_the_start() {
+    let mut simulation = Simulation::generate(20, 20, 9).expect("no free tile");
+    let mut recorder = Recorder::bounded(&simulation, 100);
+    recorder.run(&mut simulation, 350);
+    let log = recorder.log();
+    assert!(log.ticks.len() >= 100 && log.ticks.len() < 200);
+    assert_eq!(log.start_tick(), 200);
+    assert_eq!(log.end_tick(), 350);
+
+    // la photo de départ a suivi, la relecture retombe sur l'état final
+    let mut player = recorder.player(BehaviorRegistry::default(), PolicyHandle::default());
+    player.seek(350).unwrap();
+    assert_eq!(player.simulation(), &simulation);
+    assert!(matches!(player.seek(150), Err(ReplayError::OutOfRange { tick: 150 })));
+}
+
+#[test]
+fn test_recorder_player_starts_from_keyframes() {
+    let mut simulation = Simulation::generate(20, 20, 9).expect("no free tile");
+    let mut recorder = Recorder::new(&simulation);
+    recorder.run(&mut simulation, 250);
+    let mut player = recorder.player(BehaviorRegistry::default(), PolicyHandle::default());
+    player.seek(210).unwrap();
+    let at_210 = player.simulation().clone();
+
+    let mut replayed = Player::new(recorder.into_log());
+    replayed.seek(210).unwrap();
+    assert_eq!(replayed.simulation(), &at_210);
+}
+
+#[test]
+fn test_replay_keeps_the_build_policy() {
+    let mut simulation = Simulation::generate(20, 20, 9).expect("no free tile");
+    simulation.build_policy = PolicyHandle::new(FixedPolicy(BuildOrder { module: Module::Battery, behavior: Behavior::Exploration }));
+    simulation.station.energy = 1000;
+    simulation.station.ore = 20;
+    let mut recorder = Recorder::new(&simulation);
+    recorder.run(&mut simulation, 250);
+    assert!(simulation.station.production.built > 0);
+
+    let mut player = recorder.player(BehaviorRegistry::default(), simulation.build_policy.clone());
+    player.seek(250).unwrap();
+    assert_eq!(player.simulation(), &simulation);
+    // en reculant on repart d'une photo, la politique doit suivre
+    player.seek(120).unwrap();
+    player.seek(250).unwrap();
+    assert_eq!(player.simulation(), &simulation);
+
+    // sans la politique la relecture ne retombe pas sur la même partie
+    let mut default_player = Player::new(recorder.into_log());
+    let replayed = default_player.seek(250).map(|_| default_player.simulation() == &simulation);
+    assert!(!matches!(replayed, Ok(true)));
+}
@@ -1,5 +1,8 @@
 use eerea::map::Map;
 use eerea::robot::{Behavior, Module, Robot};
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 #[test]
 fn test_robot_creation() {
@@ -13,7 +16,8 @@ fn test_robot_creation() {
 fn test_robot_exploration() {
     let mut map = Map::new(10, 10, 1);
     let mut robot = Robot::new(1, (1, 1), 100, Module::Analysis, Behavior::Exploration);
-    robot.perform_action(&mut map, (0, 0));
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action(&mut map, (0, 0), &mut rng, 0);
     assert!(robot.energy < 100);
     assert!(map.tile_at(robot.position.0, robot.position.1).unwrap().explored);
 }
@@ -24,7 +28,8 @@ fn test_robot_move_towards_goal() {
     let map = Map::new(10, 10, 1);
     let mut robot = Robot::new(1, (1, 1), 100, Module::Mining, Behavior::ResourceCollection);
     let goal = (3, 3);
-    robot.move_towards_goal(&map, goal);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.move_towards_goal(&map, None, goal, &mut rng);
     assert_ne!(robot.position, (1, 1));
 }
 
@@ -33,4 +38,43 @@ fn test_robot_mark_tile_as_known() {
     let mut robot = Robot::new(1, (0, 0), 100, Module::Analysis, Behavior::Exploration);
     robot.mark_tile_as_known(2, 2);
     assert!(robot.known_tiles.contains(&(2, 2)));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_robot_mines_into_cargo() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(1, 1, Resource::Ore);
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Mining, Behavior::ResourceCollection);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action(&mut map, (5, 5), &mut rng, 0);
+    assert_eq!(robot.cargo, vec![Resource::Ore]);
+    assert_eq!(map.tile_at(1, 1).unwrap().content, TileContent::Empty);
+}
+
+#[test]
+fn test_mining_rate_depends_on_modules() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_deposit_at(1, 1, Resource::Ore, 5);
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Mining, Behavior::ResourceCollection);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action(&mut map, (5, 5), &mut rng, 0);
+    assert_eq!(robot.cargo.len(), 1);
+    assert_eq!(robot.belief.observation(1, 1).unwrap().quantity, 4);
+
+    robot.install_module(Module::Mining).unwrap();
+    robot.perform_action(&mut map, (5, 5), &mut rng, 1);
+    assert_eq!(robot.cargo.len(), 3);
+    assert_eq!(map.deposit_at(1, 1), Some((Resource::Ore, 2)));
+}
+
+#[test]
+fn test_robot_full_cargo_heads_home() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(1, 1, Resource::Ore);
+    let mut robot = Robot::new(1, (1, 1), 100, Module::Mining, Behavior::ResourceCollection);
+    robot.cargo = vec![Resource::Energy; robot.cargo_capacity];
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    robot.perform_action(&mut map, (1, 1), &mut rng, 0);
+    assert!(robot.is_cargo_full());
+    assert_eq!(map.tile_at(1, 1).unwrap().content, TileContent::Resource(Resource::Ore));
+}
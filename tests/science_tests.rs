@@ -0,0 +1,86 @@
+use eerea::behavior::{Action, RobotBehavior, ScientificBehavior, WorldView};
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::science::{Finding, FindingKind, ScienceReport, ANALYSIS_TICKS};
+use eerea::station::Station;
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::empty_map;
+
+fn map_with_point_of_interest() -> Map {
+    let mut map = empty_map(10, 10);
+    map.throw_resource_at(3, 3, Resource::PlaceOfInterest);
+    map
+}
+
+#[test]
+fn test_analysis_takes_several_ticks_and_produces_report() {
+    let mut map = map_with_point_of_interest();
+    let mut robot = Robot::new(1, (3, 3), 100, Module::Analysis, Behavior::ScientificInterest);
+    robot.sense(&mut map, 0);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    for tick in 0..ANALYSIS_TICKS as u64 - 1 {
+        robot.execute(Action::Analyze, &mut map, None, &mut rng, tick);
+        assert!(robot.reports.is_empty());
+        assert!(robot.is_analyzing());
+    }
+    robot.execute(Action::Analyze, &mut map, None, &mut rng, 10);
+
+    assert_eq!(robot.reports.len(), 1);
+    let report = &robot.reports[0];
+    assert_eq!(report.location, (3, 3));
+    assert_eq!(report.tick, 10);
+    assert!(!report.findings.is_empty());
+    assert!(!robot.can_analyze());
+    // le point d'intérêt reste sur la carte
+    assert_eq!(map.tiles[3][3].content, TileContent::Resource(Resource::PlaceOfInterest));
+}
+
+#[test]
+fn test_only_analysis_module_can_analyze() {
+    let mut map = map_with_point_of_interest();
+    let mut robot = Robot::new(1, (3, 3), 100, Module::Mining, Behavior::ScientificInterest);
+    robot.sense(&mut map, 0);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for tick in 0..10 {
+        robot.execute(Action::Analyze, &mut map, None, &mut rng, tick);
+    }
+    assert!(!robot.can_analyze());
+    assert!(robot.reports.is_empty());
+}
+
+#[test]
+fn test_scientific_behavior_brings_report_home() {
+    let mut map = map_with_point_of_interest();
+    let mut robot = Robot::new(1, (3, 3), 100, Module::Analysis, Behavior::ScientificInterest);
+    robot.sense(&mut map, 0);
+    let world = WorldView { station_position: (6, 6), tick: 0 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    assert_eq!(ScientificBehavior.decide(&robot, &world, &mut rng), Action::Analyze);
+    for tick in 0..ANALYSIS_TICKS as u64 {
+        robot.execute(Action::Analyze, &mut map, None, &mut rng, tick);
+    }
+    assert_eq!(ScientificBehavior.decide(&robot, &world, &mut rng), Action::MoveTowards((6, 6)));
+}
+
+#[test]
+fn test_station_scores_each_place_once() {
+    let mut station = Station::new((0, 0));
+    let mut robot = Robot::new(1, (0, 0), 100, Module::Analysis, Behavior::ScientificInterest);
+    let findings = vec![Finding { kind: FindingKind::Water, value: 4 }, Finding { kind: FindingKind::Fossil, value: 3 }];
+    let report = ScienceReport { id: 1, location: (3, 3), tick: 5, findings };
+    let score = report.score();
+    assert_eq!(score, 7);
+    robot.reports.push(report.clone());
+    robot.reports.push(ScienceReport { id: 2, ..report });
+
+    assert_eq!(station.receive_reports(&mut robot), score);
+    assert!(robot.reports.is_empty());
+    assert_eq!(station.science_reports.len(), 2);
+    assert_eq!(station.science_score, score);
+}
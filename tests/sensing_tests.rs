@@ -0,0 +1,58 @@
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::sensing::{has_line_of_sight, visible_tiles, FieldOfView};
+
+const ROOM: &str = "\
+###########
+#.........#
+#.........#
+#....#....#
+#.........#
+#.........#
+###########
+";
+
+#[test]
+fn test_base_chassis_sees_neighbours() {
+    let (map, _) = Map::from_ascii(ROOM).unwrap();
+    let visible = visible_tiles(&map, (2, 2), Module::Mining.field_of_view());
+    assert_eq!(visible.len(), 5);
+    assert!(visible.contains(&(2, 1)) && visible.contains(&(3, 2)));
+}
+
+#[test]
+fn test_line_of_sight_blocked_by_obstacle() {
+    let (map, _) = Map::from_ascii(ROOM).unwrap();
+    assert!(has_line_of_sight(&map, (2, 3), (5, 3)));
+    assert!(!has_line_of_sight(&map, (2, 3), (8, 3)));
+    assert!(has_line_of_sight(&map, (2, 1), (8, 1)));
+}
+
+#[test]
+fn test_imaging_sees_further_but_not_through_walls() {
+    let (map, _) = Map::from_ascii(ROOM).unwrap();
+    let fov = Module::Imaging.field_of_view();
+    assert!(fov.radius > Module::Mining.field_of_view().radius);
+    let visible = visible_tiles(&map, (3, 3), fov);
+    assert!(visible.contains(&(5, 3)));
+    assert!(!visible.contains(&(7, 3)));
+    assert!(visible.contains(&(6, 2)));
+
+    let open = visible_tiles(&map, (3, 3), FieldOfView { line_of_sight: false, ..fov });
+    assert!(open.contains(&(7, 3)));
+}
+
+#[test]
+fn test_sensing_marks_explored_and_known() {
+    let (mut map, _) = Map::from_ascii(ROOM).unwrap();
+    let mut scout = Robot::new(1, (3, 3), 100, Module::Imaging, Behavior::Exploration);
+    let mut miner = Robot::new(2, (3, 3), 100, Module::Mining, Behavior::ResourceCollection);
+    scout.sense(&mut map, 7);
+    miner.sense(&mut map, 7);
+    assert!(scout.known_tiles.len() > miner.known_tiles.len());
+    assert!(scout.known_tiles.contains(&(5, 3)));
+    let tile = map.tile_at(1, 3).unwrap();
+    assert!(tile.explored);
+    assert_eq!(tile.timestamp, 7);
+    assert_eq!(scout.belief.known_count(), scout.known_tiles.len());
+}
@@ -0,0 +1,66 @@
+use eerea::map::Map;
+use eerea::simulation::Simulation;
+use eerea::station::Station;
+use eerea::tile::TileContent;
+
+#[test]
+fn test_simulation_generate() {
+    let simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    assert_eq!(simulation.tick, 0);
+    assert_eq!(simulation.station.robots.len(), 3);
+    assert!(simulation.map.is_empty(simulation.station.position.0, simulation.station.position.1));
+}
+
+#[test]
+fn test_simulation_step() {
+    let map = Map::new(10, 10, 1);
+    let station = Station::new(map.find_free_tile().unwrap());
+    let mut simulation = Simulation::new(map, station, 1);
+    simulation.step();
+    assert_eq!(simulation.tick, 1);
+}
+
+#[test]
+fn test_simulation_run_headless() {
+    let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    simulation.run(1000);
+    assert_eq!(simulation.tick, 1000);
+    for robot in &simulation.station.robots {
+        let tile = simulation.map.tile_at(robot.position.0, robot.position.1).unwrap();
+        assert_ne!(tile.content, TileContent::Obstacle);
+    }
+}
+
+#[test]
+fn test_simulation_is_deterministic() {
+    let mut first = Simulation::generate(20, 20, 7).expect("no free tile");
+    let mut second = Simulation::generate(20, 20, 7).expect("no free tile");
+    first.run(300);
+    second.run(300);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_simulation_seed_changes_the_world() {
+    let first = Simulation::generate(20, 20, 7).expect("no free tile");
+    let second = Simulation::generate(20, 20, 8).expect("no free tile");
+    assert_ne!(first.map, second.map);
+}
+
+#[test]
+fn test_simulation_collectors_deliver_to_station() {
+    let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    simulation.run(500);
+    assert!(simulation.station.energy > 0 || simulation.station.ore > 0 || simulation.station.robots.len() > 3);
+}
+
+#[test]
+fn test_simulation_from_ascii_map() {
+    let (map, station) = Map::from_ascii(include_str!("fixtures/corridor.txt")).unwrap();
+    let mut simulation = Simulation::from_map(map, station.unwrap(), 1);
+    assert_eq!(simulation.station.position, (1, 1));
+    simulation.run(200);
+    // ce qui a été livré est en stock ou déjà dépensé dans l'atelier
+    let production = &simulation.station.production;
+    assert!(simulation.station.energy > 0 || simulation.station.ore > 0 || production.current.is_some() || production.built > 0);
+}
@@ -0,0 +1,71 @@
+use eerea::map::Map;
+use eerea::simulation::Simulation;
+use eerea::snapshot::{Snapshot, SnapshotError, SNAPSHOT_VERSION};
+
+#[test]
+fn test_snapshot_json_roundtrip() {
+    let mut simulation = Simulation::generate(20, 20, 3).expect("no free tile");
+    simulation.run(50);
+    let json = simulation.snapshot().to_json().unwrap();
+    let restored = Snapshot::from_json(&json).unwrap().restore();
+    assert_eq!(restored, simulation);
+}
+
+#[test]
+fn test_snapshot_binary_roundtrip() {
+    let mut simulation = Simulation::generate(20, 20, 3).expect("no free tile");
+    simulation.run(50);
+    let bytes = simulation.snapshot().to_bytes().unwrap();
+    let restored = Snapshot::from_bytes(&bytes).unwrap().restore();
+    assert_eq!(restored, simulation);
+}
+
+#[test]
+fn test_snapshot_resume_matches_uninterrupted_run() {
+    let mut uninterrupted = Simulation::generate(20, 20, 5).expect("no free tile");
+    uninterrupted.run(200);
+
+    let mut first_half = Simulation::generate(20, 20, 5).expect("no free tile");
+    first_half.run(100);
+    let bytes = first_half.snapshot().to_bytes().unwrap();
+    let mut resumed = Snapshot::from_bytes(&bytes).unwrap().restore();
+    resumed.run(100);
+
+    assert_eq!(resumed, uninterrupted);
+}
+
+#[test]
+fn test_snapshot_save_and_load_file() {
+    let simulation = Simulation::generate(20, 20, 3).expect("no free tile");
+    let path = std::env::temp_dir().join("eerea_snapshot_test.json");
+    simulation.snapshot().save_json(&path).unwrap();
+    let loaded = Snapshot::load_json(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.version, SNAPSHOT_VERSION);
+    assert_eq!(loaded.restore(), simulation);
+}
+
+#[test]
+fn test_snapshot_rejects_unknown_version() {
+    let simulation = Simulation::generate(20, 20, 3).expect("no free tile");
+    let mut snapshot = simulation.snapshot();
+    snapshot.version = SNAPSHOT_VERSION + 1;
+    let json = snapshot.to_json().unwrap();
+    assert!(matches!(Snapshot::from_json(&json), Err(SnapshotError::UnsupportedVersion(_))));
+    assert!(matches!(Snapshot::from_bytes(b"nope"), Err(SnapshotError::InvalidFormat)));
+}
+
+// le JSON d'une petite partie fixe doit rester celui enregistré pour la version courante.
+// S'il change : incrémenter SNAPSHOT_VERSION (une fois par version publiée) puis écrire le fichier avec EEREA_BLESS=1 cargo test
+#[test]
+fn test_snapshot_format_matches_its_version() {
+    let (map, station) = Map::from_ascii("#####\n#S.O#\n#####\n").unwrap();
+    let json = Simulation::from_map(map, station.unwrap(), 1).snapshot().to_json().unwrap();
+    let path = format!("{}/tests/fixtures/snapshot_v{}.json", env!("CARGO_MANIFEST_DIR"), SNAPSHOT_VERSION);
+    if std::env::var_os("EEREA_BLESS").is_some() {
+        std::fs::write(&path, &json).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(json == expected, "snapshot format changed without bumping SNAPSHOT_VERSION ({})", path);
+    assert_eq!(Snapshot::from_json(&expected).unwrap().version, SNAPSHOT_VERSION);
+}
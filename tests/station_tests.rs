@@ -1,6 +1,7 @@
 use eerea::map::Map;
 use eerea::robot::{Behavior, Module, Robot};
-use eerea::station::Station;
+use eerea::station::{Station, ENERGY_PER_DELIVERY};
+use eerea::tile::Resource;
 
 #[test]
 fn test_station_initialization() {
@@ -15,10 +16,13 @@ fn test_station_initialization() {
 fn test_station_collect_data() {
     let mut map = Map::new(10, 10, 1);
     let mut robot = Robot::new(1, (1, 1), 100, Module::Analysis, Behavior::Exploration);
-    robot.mark_explored(&mut map);
+    robot.sense(&mut map, 3);
     let mut station = Station::new((5, 5));
-    station.collect_data(&robot, &map);
-    assert!(station.known_tiles.iter().any(|t| t.x == 1 && t.y == 1));
+    station.collect_data(&robot);
+    let known_tile = station.known_tiles.get(1, 1).expect("tile not collected");
+    assert_eq!(known_tile.content, map.tiles[1][1].content);
+    assert_eq!(known_tile.observer, 1);
+    assert_eq!(known_tile.tick, 3);
 }
 
 #[test]
@@ -27,7 +31,7 @@ fn test_station_create_robot() {
     let robot = station.create_robot(1, (5, 5), Module::Mining, Behavior::ResourceCollection);
     assert_eq!(robot.id, 1);
     assert_eq!(robot.position, (5, 5));
-    assert_eq!(robot.energy, 100);
+    assert_eq!(robot.energy, Module::Mining.battery_capacity());
     assert_eq!(station.robots.len(), 1);
 }
 
@@ -36,4 +40,15 @@ fn test_station_add_energy() {
     let mut station = Station::new((5, 5));
     station.add_energy(50);
     assert_eq!(station.energy, 50);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_station_unload_cargo() {
+    let mut station = Station::new((5, 5));
+    let mut robot = Robot::new(1, (5, 5), 100, Module::Mining, Behavior::ResourceCollection);
+    robot.cargo = vec![Resource::Energy, Resource::Ore, Resource::Ore];
+    station.unload_cargo(&mut robot);
+    assert!(robot.cargo.is_empty());
+    assert_eq!(station.energy, ENERGY_PER_DELIVERY);
+    assert_eq!(station.ore, 2);
+}
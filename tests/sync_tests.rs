@@ -0,0 +1,113 @@
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::station::Station;
+use eerea::sync::{self, SyncConfig, SyncMessage};
+use eerea::tile::{Resource, TileContent};
+
+mod common;
+use common::empty_map;
+
+#[test]
+fn test_sync_uploads_then_shares_with_other_robots() {
+    let mut map = empty_map(10, 10);
+    map.throw_resource_at(8, 8, Resource::Ore);
+    let mut scout = Robot::new(1, (8, 8), 100, Module::Imaging, Behavior::Exploration);
+    scout.sense(&mut map, 1);
+    let mut miner = Robot::new(2, (5, 5), 100, Module::Mining, Behavior::ResourceCollection);
+    miner.sense(&mut map, 1);
+    let mut station = Station::new((5, 5));
+    let config = SyncConfig::default();
+
+    let messages = sync::sync(&mut station, &mut scout, &config, 2);
+    assert!(matches!(messages[0], SyncMessage::Upload { robot_id: 1, .. }));
+    assert_eq!(messages[0].tiles().len(), scout.belief.known_count());
+
+    assert_eq!(miner.find_resource(), None);
+    sync::sync(&mut station, &mut miner, &config, 3);
+    assert_eq!(miner.find_resource(), Some((8, 8)));
+}
+
+#[test]
+fn test_second_sync_only_sends_new_observations() {
+    let mut map = Map::new(10, 10, 1);
+    let mut robot = Robot::new(1, (2, 2), 100, Module::Mining, Behavior::Exploration);
+    robot.sense(&mut map, 1);
+    let mut station = Station::new((5, 5));
+    let config = SyncConfig::default();
+
+    sync::sync(&mut station, &mut robot, &config, 1);
+    assert!(sync::sync(&mut station, &mut robot, &config, 2).is_empty());
+
+    robot.position = (6, 6);
+    robot.sense(&mut map, 3);
+    let messages = sync::sync(&mut station, &mut robot, &config, 3);
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].tiles().iter().all(|update| update.tile.tick == 3));
+}
+
+#[test]
+fn test_bandwidth_limit_spreads_upload_over_ticks() {
+    let mut map = Map::new(10, 10, 1);
+    let mut robot = Robot::new(1, (5, 5), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 1);
+    let total = robot.belief.known_count();
+    let mut station = Station::new((5, 5));
+    let config = SyncConfig { upload_per_tick: Some(10), download_per_tick: Some(10) };
+
+    let mut ticks = 0;
+    while station.known_tiles.len() < total {
+        let messages = sync::sync(&mut station, &mut robot, &config, 2 + ticks);
+        assert!(messages.iter().all(|message| message.tiles().len() <= 10));
+        ticks += 1;
+    }
+    assert_eq!(ticks as usize, total.div_ceil(10));
+    assert_eq!(station.sync_stats.uploaded_tiles as usize, total);
+}
+
+#[test]
+fn test_downloaded_tile_does_not_override_newer_belief() {
+    let mut map = Map::new(10, 10, 1);
+    map.throw_resource_at(3, 3, Resource::Energy);
+    let mut old = Robot::new(1, (3, 3), 100, Module::Mining, Behavior::Exploration);
+    old.sense(&mut map, 1);
+    let mut station = Station::new((5, 5));
+    sync::sync(&mut station, &mut old, &SyncConfig::default(), 1);
+
+    let mut fresh = Robot::new(2, (3, 3), 100, Module::Mining, Behavior::Exploration);
+    map.retrieve_resource_at(3, 3);
+    fresh.sense(&mut map, 5);
+    fresh.receive_observation(3, 3, TileContent::Resource(Resource::Energy), 1);
+    assert_eq!(fresh.belief.content_at(3, 3), Some(TileContent::Empty));
+}
+
+#[test]
+fn test_simulation_records_sync_messages() {
+    let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+    simulation.run(300);
+    let stats = simulation.station.sync_stats;
+    assert!(stats.messages > 0);
+    assert!(stats.uploaded_tiles > 0);
+    assert!(stats.downloaded_tiles > 0);
+    assert!(!simulation.station.known_tiles.is_empty());
+}
+
+#[test]
+fn test_robot_without_belief_does_not_wipe_station_map() {
+    let mut map = empty_map(10, 10);
+    let mut scout = Robot::new(1, (2, 2), 100, Module::Imaging, Behavior::Exploration);
+    scout.sense(&mut map, 1);
+    let mut station = Station::new((5, 5));
+    let config = SyncConfig::default();
+    sync::sync(&mut station, &mut scout, &config, 1);
+    let known = station.known_tiles.len();
+
+    // pas encore de croyance, ou une croyance d'une autre carte : on ignore le robot
+    let mut newcomer = Robot::new(2, (5, 5), 100, Module::Mining, Behavior::ResourceCollection);
+    assert!(sync::sync(&mut station, &mut newcomer, &config, 2).is_empty());
+    newcomer.sense(&mut empty_map(20, 20), 2);
+    assert!(sync::sync(&mut station, &mut newcomer, &config, 3).is_empty());
+    station.collect_data(&newcomer);
+    assert_eq!(station.known_tiles.len(), known);
+    assert_eq!((station.known_tiles.width, station.known_tiles.height), (10, 10));
+}
@@ -0,0 +1,119 @@
+use eerea::behavior::{CollectionBehavior, RobotBehavior, WorldView};
+use eerea::behavior::Action;
+use eerea::knowledge::{KnowledgeBase, KnownTile};
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::station::Station;
+use eerea::tasks::{Allocation, TaskBoard, TaskKind, MAX_OPEN_MINE_TASKS};
+use eerea::tile::{Resource, TileContent};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn miner(id: usize, position: (usize, usize)) -> Robot {
+    Robot::new(id, position, 100, Module::Mining, Behavior::ResourceCollection)
+}
+
+// deux tâches sur une ligne : le plus proche d'abord n'est pas le meilleur total
+fn board(allocation: Allocation) -> (TaskBoard, Vec<Robot>) {
+    let mut board = TaskBoard { allocation, ..TaskBoard::default() };
+    board.add(TaskKind::MineAt((2, 0)));
+    board.add(TaskKind::MineAt((10, 0)));
+    (board, vec![miner(1, (0, 0)), miner(2, (3, 0))])
+}
+
+#[test]
+fn test_greedy_takes_nearest_pair_first() {
+    let (mut board, robots) = board(Allocation::GreedyNearest);
+    let mut pairs = board.allocate(&robots, (0, 0), 0);
+    pairs.sort();
+    assert_eq!(pairs, vec![(0, 1), (1, 0)]);
+}
+
+#[test]
+fn test_hungarian_minimises_total_distance() {
+    let (mut board, mut robots) = board(Allocation::Hungarian);
+    // plus de robots que de tâches
+    robots.push(miner(3, (30, 0)));
+    let mut pairs = board.allocate(&robots, (0, 0), 0);
+    pairs.sort();
+    assert_eq!(pairs, vec![(0, 0), (1, 1)]);
+    assert!(board.tasks.iter().all(|task| task.assigned.is_some()));
+}
+
+#[test]
+fn test_auction_skips_robots_without_the_module() {
+    let mut board = TaskBoard { allocation: Allocation::Auction, ..TaskBoard::default() };
+    board.add(TaskKind::MineAt((2, 0)));
+    board.add(TaskKind::AnalysePoi((5, 0)));
+    let robots = vec![Robot::new(1, (2, 0), 100, Module::Analysis, Behavior::ScientificInterest), miner(2, (9, 0))];
+    let mut pairs = board.allocate(&robots, (0, 0), 0);
+    pairs.sort();
+    assert_eq!(pairs, vec![(0, 1), (1, 0)]);
+}
+
+#[test]
+fn test_station_gives_each_miner_its_own_deposit() {
+    let mut station = Station::new((5, 5));
+    station.known_tiles = KnowledgeBase::new(20, 20);
+    for position in [(1, 5), (9, 5)] {
+        let tile = KnownTile { content: TileContent::Resource(Resource::Ore), observer: 9, tick: 0 };
+        station.known_tiles.merge(position.0, position.1, tile);
+    }
+    station.robots = vec![miner(1, (5, 5)), miner(2, (5, 5)), miner(3, (5, 5))];
+    station.plan_tasks(0);
+
+    let targets: Vec<_> = station.robots.iter().filter_map(|robot| robot.task).map(|task| task.kind).collect();
+    assert_eq!(targets.len(), 2);
+    assert!(targets.contains(&TaskKind::MineAt((1, 5))));
+    assert!(targets.contains(&TaskKind::MineAt((9, 5))));
+}
+
+#[test]
+fn test_full_cargo_gets_return_task_and_completes_on_unload() {
+    let mut station = Station::new((5, 5));
+    let mut robot = miner(1, (8, 8));
+    robot.cargo = vec![Resource::Ore; robot.cargo_capacity];
+    station.robots.push(robot);
+    station.plan_tasks(0);
+
+    let robot = &station.robots[0];
+    assert_eq!(robot.task.map(|task| task.kind), Some(TaskKind::ReturnCargo));
+    let world = WorldView { station_position: (5, 5), tick: 0 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    assert_eq!(CollectionBehavior.decide(robot, &world, &mut rng), Action::MoveTowards((5, 5)));
+
+    let mut robot = station.robots.remove(0);
+    station.unload_cargo(&mut robot);
+    station.robots.push(robot);
+    station.plan_tasks(1);
+    assert_eq!(station.task_board.completed, 1);
+    assert!(station.robots[0].task.is_none());
+}
+
+#[test]
+fn test_open_mine_tasks_are_capped_nearest_first() {
+    let mut station = Station::new((0, 0));
+    station.known_tiles = KnowledgeBase::new(40, 40);
+    for x in 0..40 {
+        let tile = KnownTile { content: TileContent::Resource(Resource::Ore), observer: 9, tick: 0 };
+        station.known_tiles.merge(x, 5, tile);
+    }
+    station.plan_tasks(0);
+    let mines: Vec<_> = station
+        .task_board
+        .tasks
+        .iter()
+        .filter_map(|task| match task.kind {
+            TaskKind::MineAt(position) => Some(position),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(mines.len(), MAX_OPEN_MINE_TASKS);
+    assert!(mines.iter().all(|&(x, _)| x < MAX_OPEN_MINE_TASKS));
+
+    // un gisement vidé quitte la liste, le suivant prend sa place
+    station.known_tiles.merge(0, 5, KnownTile { content: TileContent::Empty, observer: 9, tick: 1 });
+    station.plan_tasks(1);
+    assert!(!station.task_board.sites.contains(&(0, 5)));
+    assert!(!station.task_board.contains(&TaskKind::MineAt((0, 5))));
+    assert!(station.task_board.contains(&TaskKind::MineAt((MAX_OPEN_MINE_TASKS, 5))));
+}
@@ -3,7 +3,7 @@ use eerea::tile::{Tile, TileContent, Resource};
 #[test]
 fn test_tile_initialization() {
     let tile = Tile::new(false, TileContent::Empty);
-    assert_eq!(tile.explored, false);
+    assert!(!tile.explored);
     assert_eq!(tile.content, TileContent::Empty);
 }
 
@@ -0,0 +1,122 @@
+use eerea::behavior::{Action, RobotBehavior, WorldView};
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::tile::TileContent;
+use eerea::traffic::ReservationTable;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::empty_map;
+
+// le robot 1 va au bout du couloir, les autres reviennent vers la station
+struct CrossCorridor;
+
+impl RobotBehavior for CrossCorridor {
+    fn decide(&self, robot: &Robot, world: &WorldView, _rng: &mut dyn RngCore) -> Action {
+        if robot.id == 1 {
+            Action::MoveTowards((9, 1))
+        } else {
+            Action::MoveTowards(world.station_position)
+        }
+    }
+}
+
+#[test]
+fn test_station_tile_is_never_reserved() {
+    let map = empty_map(10, 10);
+    let robots = vec![
+        Robot::new(1, (5, 5), 100, Module::Imaging, Behavior::Exploration),
+        Robot::new(2, (2, 2), 100, Module::Imaging, Behavior::Exploration),
+        Robot::new(3, (2, 2), 100, Module::Imaging, Behavior::Exploration),
+    ];
+    let mut table = ReservationTable::from_robots(&map, (5, 5), 2, &robots);
+    assert_eq!(table.holder((5, 5)), None);
+    assert_eq!(table.blocker((2, 2), 1), Some(3));
+    assert!(table.is_free_for((2, 2), 3));
+
+    table.move_robot(3, (2, 2), (2, 3));
+    assert!(table.is_free_for((2, 2), 1));
+    assert_eq!(table.holder((2, 3)), Some(3));
+}
+
+#[test]
+fn test_robot_waits_then_takes_a_detour() {
+    let mut map = empty_map(10, 10);
+    let blocker = Robot::new(1, (3, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    let mut robot = Robot::new(2, (2, 2), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 0);
+    let reservations = ReservationTable::from_robots(&map, (0, 0), 2, &[blocker, robot.clone()]);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    for _ in 0..2 {
+        robot.move_towards_goal(&map, Some(&reservations), (5, 2), &mut rng);
+        assert_eq!(robot.position, (2, 2));
+    }
+    robot.move_towards_goal(&map, Some(&reservations), (5, 2), &mut rng);
+    assert_ne!(robot.position, (2, 2));
+    assert_ne!(robot.position, (3, 2));
+    assert_eq!(robot.traffic.waits, 3);
+    assert_eq!(robot.traffic.detours, 1);
+}
+
+#[test]
+fn test_detour_does_not_step_onto_an_unseen_obstacle() {
+    let mut map = empty_map(10, 10);
+    let blocker = Robot::new(1, (3, 2), 100, Module::Mining, Behavior::ResourceCollection);
+    let mut robot = Robot::new(2, (2, 2), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 0);
+    // le robot ne sait pas que les deux côtés sont bouchés
+    map.tiles[1][2].content = TileContent::Obstacle;
+    map.tiles[3][2].content = TileContent::Obstacle;
+    let reservations = ReservationTable::from_robots(&map, (0, 0), 0, &[blocker, robot.clone()]);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    robot.move_towards_goal(&map, Some(&reservations), (5, 2), &mut rng);
+    assert_eq!(robot.position, (2, 2));
+    assert!(robot.route.is_none());
+    assert_eq!(robot.traffic.detours, 0);
+}
+
+#[test]
+fn test_robots_pass_each_other_in_a_corridor() {
+    let text = "\
+###########
+#S........#
+#####.#####
+###########
+";
+    let (map, station) = Map::from_ascii(text).unwrap();
+    let mut simulation = Simulation::from_map(map, station.unwrap(), 5);
+    simulation.traffic_config.enabled = true;
+    simulation.behaviors.register("cross", CrossCorridor);
+    simulation.station.robots = vec![
+        Robot::new(1, (2, 1), 100, Module::Imaging, Behavior::Custom("cross".to_string())),
+        Robot::new(2, (8, 1), 100, Module::Imaging, Behavior::Custom("cross".to_string())),
+    ];
+
+    let mut collided = false;
+    for _ in 0..40 {
+        simulation.step();
+        let robots = &simulation.station.robots;
+        collided |= robots[0].position == robots[1].position && robots[0].position != simulation.station.position;
+    }
+
+    assert!(!collided);
+    assert_eq!(simulation.station.robots[0].position, (9, 1));
+    assert_eq!(simulation.station.robots[1].position, station.unwrap());
+    assert!(simulation.traffic_stats().yields > 0);
+}
+
+#[test]
+fn test_robots_share_tiles_when_traffic_is_off() {
+    let mut map = empty_map(10, 10);
+    let mut robot = Robot::new(2, (2, 2), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 0);
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    // sans table de réservation personne ne bloque le passage
+    robot.move_towards_goal(&map, None, (5, 2), &mut rng);
+    assert_eq!(robot.position, (3, 2));
+    assert_eq!(robot.traffic.waits, 0);
+}
@@ -0,0 +1,110 @@
+use eerea::map::Map;
+use eerea::robot::{Behavior, Module, Robot};
+use eerea::simulation::Simulation;
+use eerea::tile::{Resource, TileContent};
+use eerea::world_events::{DustStorm, WorldEvents, WorldEventsConfig};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+mod common;
+use common::empty_map;
+
+// rien ne se passe sauf ce que le test active
+fn quiet() -> WorldEventsConfig {
+    WorldEventsConfig {
+        enabled: true,
+        energy_regen_chance: 0.0,
+        new_poi_chance: 0.0,
+        dust_storm_chance: 0.0,
+        rockslide_chance: 0.0,
+        ..WorldEventsConfig::default()
+    }
+}
+
+#[test]
+fn test_energy_sites_regenerate_up_to_the_cap() {
+    let mut map = empty_map(10, 10);
+    map.throw_resource_at(3, 3, Resource::Energy);
+    let config = WorldEventsConfig { energy_regen_chance: 1.0, max_energy_deposit: 2, ..quiet() };
+    let mut events = WorldEvents::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    events.update(&mut map, &config, &[], &mut rng, 0);
+    map.retrieve_resource_at(3, 3);
+    map.retrieve_resource_at(3, 3);
+    assert_eq!(map.deposit_at(3, 3), None);
+
+    for tick in 1..5 {
+        events.update(&mut map, &config, &[], &mut rng, tick);
+    }
+    assert_eq!(map.deposit_at(3, 3), Some((Resource::Energy, 2)));
+    assert_eq!(events.stats.regenerated, 3);
+}
+
+#[test]
+fn test_energy_mined_out_before_the_first_update_still_regenerates() {
+    let (mut map, _) = Map::from_ascii("....\n.E..\n....\n").unwrap();
+    assert_eq!(map.retrieve_resource_at(1, 1), Some(Resource::Energy));
+    assert_eq!(map.deposit_at(1, 1), None);
+    // la liste des sites voyage avec la carte, comme dans un snapshot
+    let mut map = Map::from_json(&map.to_json().unwrap()).unwrap();
+
+    let config = WorldEventsConfig { energy_regen_chance: 1.0, ..quiet() };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    WorldEvents::default().update(&mut map, &config, &[], &mut rng, 0);
+    assert_eq!(map.deposit_at(1, 1), Some((Resource::Energy, 1)));
+}
+
+#[test]
+fn test_points_of_interest_and_rockslides_avoid_occupied_tiles() {
+    let mut map = empty_map(10, 10);
+    let occupied: Vec<_> = (0..10).flat_map(|y| (0..10).map(move |x| (x, y))).filter(|&(x, y)| (x, y) != (4, 4) && (x, y) != (7, 7)).collect();
+    let config = WorldEventsConfig { new_poi_chance: 1.0, rockslide_chance: 1.0, rockslide_size: 3, ..quiet() };
+    let mut events = WorldEvents::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    events.update(&mut map, &config, &occupied, &mut rng, 0);
+
+    let changed: Vec<_> = (0..10)
+        .flat_map(|y| (0..10).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.tiles[y][x].content != TileContent::Empty)
+        .collect();
+    assert_eq!(changed, vec![(4, 4), (7, 7)]);
+    assert_eq!((events.stats.new_points_of_interest, events.stats.rockslides), (1, 1));
+}
+
+#[test]
+fn test_dust_storm_blinds_sensors_until_it_ends() {
+    let mut map = empty_map(10, 10);
+    map.storms.push(DustStorm { centre: (5, 5), radius: 2, until: 10 });
+    let mut robot = Robot::new(1, (5, 6), 100, Module::Imaging, Behavior::Exploration);
+    robot.sense(&mut map, 0);
+    assert_eq!(robot.belief.known_count(), 1);
+
+    let mut events = WorldEvents::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    events.update(&mut map, &quiet(), &[], &mut rng, 10);
+    assert!(map.storms.is_empty());
+    robot.sense(&mut map, 10);
+    assert!(robot.belief.known_count() > 1);
+}
+
+#[test]
+fn test_world_events_follow_the_seed() {
+    let run = || {
+        let mut simulation = Simulation::generate(20, 20, 14).expect("no free tile");
+        simulation.world_events_config = WorldEventsConfig {
+            enabled: true,
+            new_poi_chance: 0.05,
+            dust_storm_chance: 0.05,
+            rockslide_chance: 0.05,
+            ..WorldEventsConfig::default()
+        };
+        simulation.run(200);
+        simulation
+    };
+    let (first, second) = (run(), run());
+    assert_eq!(first.map, second.map);
+    assert_eq!(first.world_events, second.world_events);
+    assert!(first.world_events.stats.rockslides > 0);
+    assert!(first.world_events.stats.dust_storms > 0);
+}